@@ -1,50 +1,163 @@
 extern crate getopts;
+#[cfg(feature = "json")]
+extern crate serde_json;
 extern crate transproblem;
 
 use getopts::Options;
-use transproblem::Transportation;
-use transproblem::Error;
+use transproblem::{Block, CandidateList, CostScaling, Dantzig, FirstNegative, Location, Metric, Parallel, PotentialMethod, Pricing, Solver, SteepestEdge,
+                   SuccessiveShortestPath, Transportation};
+use transproblem::{parse_csv, parse_lp, parse_mps, Error, Imported};
+#[cfg(feature = "json")]
+use transproblem::{JsonProblem, JsonSolution};
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{self, BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Suppliers, customers and the matrix of costs
+type Problem = (Vec<u64>, Vec<u64>, Vec<Vec<u64>>);
+// Reads a problem from a file
+type Reader = fn(&PathBuf) -> Result<Input, Box<dyn std::error::Error>>;
+
+// The problem and the labels of the suppliers and the customers, empty if the format has none.
+// The pricing rule and the solver may be given by the JSON format.
+struct Input {
+    t: Transportation,
+    suppliers: Vec<String>,
+    customers: Vec<String>,
+    rule: Option<String>,
+    method: Option<String>,
+}
+
+impl From<Imported> for Input {
+    fn from((t, suppliers, customers): Imported) -> Input {
+        Input {
+            t,
+            suppliers,
+            customers,
+            rule: None,
+            method: None,
+        }
+    }
+}
+
+// The format of the solutions
+#[derive(Clone, Copy, PartialEq)]
+enum Output {
+    Text,
+    Csv,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+// Build the structure, the errors are explained
+fn problem(t: Result<Transportation, Error>) -> Result<Input, Box<dyn std::error::Error>> {
+    match t {
+        Ok(t) => Ok(Input::from((t, Vec::new(), Vec::new()))),
+        Err(Error::NumOfSupOrCust) => Err(From::from("количество поставщиков и потребителей не должно быть меньше 0")),
+        Err(Error::NumOfRows) => Err(From::from("количество поставщиков не равно количеству строк в матрице стоимостей")),
+        Err(Error::NumOfCols) => Err(From::from("количество потребителей не равно количеству столбцов в матрице стоимостей")),
+        Err(Error::Rate) => Err(From::from("стоимость единицы расстояния должна быть неотрицательным числом")),
+        Err(err) => Err(From::from(err)),
+    }
+}
 
 // Try to read a file into a vector of strings. If successful, then try to parse them.
-fn file_input(file: &PathBuf) -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), Box<std::error::Error>> {
-    let f = BufReader::new(try!(File::open(file)));
-    let mut lines: VecDeque<String> = try!(f.lines().collect());
-    lines = lines.into_iter()
-                 .filter(|line| {
-                     line.trim();
-                     !line.is_empty()
-                 })
-                 .collect();
-
-    let a: Vec<u64> = try!(lines.pop_front()
-                                .unwrap_or("".to_owned())
-                                .split_whitespace()
-                                .map(str::parse)
-                                .collect());
-
-    let b: Vec<u64> = try!(lines.pop_front()
-                                .unwrap_or("".to_owned())
-                                .split_whitespace()
-                                .map(str::parse)
-                                .collect());
-
-    let c: Vec<Vec<u64>> = try!(lines.iter()
-                                     .map(|line| {
-                                         line.split_whitespace()
-                                             .map(|number| number.parse())
-                                             .collect()
-                                     })
-                                     .collect());
+fn file_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    let f = BufReader::new(File::open(file)?);
+    let mut lines: VecDeque<String> = f.lines().collect::<Result<_, _>>()?;
+    lines.retain(|line| !line.trim().is_empty());
 
-    Ok((a, b, c))
+    let a: Vec<u64> = lines.pop_front()
+                           .unwrap_or_default()
+                           .split_whitespace()
+                           .map(str::parse)
+                           .collect::<Result<_, _>>()?;
+
+    let b: Vec<u64> = lines.pop_front()
+                           .unwrap_or_default()
+                           .split_whitespace()
+                           .map(str::parse)
+                           .collect::<Result<_, _>>()?;
+
+    let c: Vec<Vec<u64>> = lines.iter()
+                                .map(|line| line.split_whitespace().map(|number| number.parse()).collect())
+                                .collect::<Result<_, _>>()?;
+
+    problem(Transportation::new(a, b, c))
+}
+
+// Read a list of locations. The first line is the metric (euclidean, manhattan or haversine)
+// and the cost of a unit per unit of distance, then every line is a supplier (A) or a customer (B):
+// A|B x y amount [handling cost of a unit]
+fn locations_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    let f = BufReader::new(File::open(file)?);
+    let mut lines: VecDeque<String> = f.lines().collect::<Result<_, _>>()?;
+    lines.retain(|line| !line.trim().is_empty());
+
+    let header = lines.pop_front().unwrap_or_default();
+    let mut header = header.split_whitespace();
+    let metric = match header.next() {
+        Some("euclidean") => Metric::Euclidean,
+        Some("manhattan") => Metric::Manhattan,
+        Some("haversine") => Metric::Haversine,
+        _ => return Err(From::from("неизвестная метрика расстояний")),
+    };
+    let rate: f64 = header.next().unwrap_or("1").parse()?;
+
+    let mut suppliers: Vec<Location> = Vec::new();
+    let mut customers: Vec<Location> = Vec::new();
+    for line in &lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.len() != 4 && words.len() != 5 {
+            return Err(From::from(format!("неверная строка: {}", line)));
+        }
+        let handling = match words.get(4) {
+            Some(h) => h.parse()?,
+            None => 0,
+        };
+        let location = (words[1].parse()?, words[2].parse()?, words[3].parse()?, handling);
+        match words[0] {
+            "A" => suppliers.push(location),
+            "B" => customers.push(location),
+            _ => return Err(From::from(format!("неверная строка: {}", line))),
+        }
+    }
+
+    problem(Transportation::with_locations(suppliers, customers, metric, rate))
+}
+
+// Read a transportation problem written as a linear program in the CPLEX LP format
+fn lp_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    Ok(Input::from(parse_lp(&std::fs::read_to_string(file)?)?))
+}
+
+// Read a transportation problem written as a linear program in the free MPS format
+fn mps_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    Ok(Input::from(parse_mps(&std::fs::read_to_string(file)?)?))
+}
+
+// Read a table of costs with the supply column and the demand row, see parse_csv
+fn csv_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    Ok(Input::from(parse_csv(&std::fs::read_to_string(file)?)?))
+}
+
+// Read a problem in the JSON format described in README.md
+#[cfg(feature = "json")]
+fn json_input(file: &PathBuf) -> Result<Input, Box<dyn std::error::Error>> {
+    let p = JsonProblem::from_json(&std::fs::read_to_string(file)?)?;
+    Ok(Input {
+        t: p.transportation()?,
+        suppliers: p.suppliers,
+        customers: p.customers,
+        rule: p.options.pricing,
+        method: p.options.solver,
+    })
 }
 
 // Try to read from stdin. If successful, then try to parse. Read it again if parse failed.
-fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
+fn console_input() -> Result<Problem, io::Error> {
     let mut a: Vec<u64>;
     let mut b: Vec<u64>;
     let mut c: Vec<Vec<u64>>;
@@ -53,9 +166,9 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
     println!("Введите количество поставщиков");
     loop {
         print!("A = ");
-        try!(io::stdout().flush());
+        io::stdout().flush()?;
         buffer.clear();
-        try!(io::stdin().read_line(&mut buffer));
+        io::stdin().read_line(&mut buffer)?;
         match buffer.trim().parse() {
             Ok(size) => {
                 if size != 0 {
@@ -72,9 +185,9 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
     println!("Введите количество потребителей");
     loop {
         print!("B = ");
-        try!(io::stdout().flush());
+        io::stdout().flush()?;
         buffer.clear();
-        try!(io::stdin().read_line(&mut buffer));
+        io::stdin().read_line(&mut buffer)?;
         match buffer.trim().parse() {
             Ok(size) => {
                 if size != 0 {
@@ -89,15 +202,15 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
     }
 
     println!("Введите количество груза у каждого поставщика");
-    for i in 0..a.len() {
+    for (i, ai) in a.iter_mut().enumerate() {
         loop {
             print!("a[{}] = ", i + 1);
-            try!(io::stdout().flush());
+            io::stdout().flush()?;
             buffer.clear();
-            try!(io::stdin().read_line(&mut buffer));
+            io::stdin().read_line(&mut buffer)?;
             match buffer.trim().parse() {
                 Ok(amount) => {
-                    a[i] = amount;
+                    *ai = amount;
                     break;
                 }
                 Err(_) => println!("Ошибка: количество груза должно быть целым неотрицательным числом"),
@@ -106,15 +219,15 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
     }
 
     println!("Введите количество заказоного груза у каждого потребителя");
-    for i in 0..b.len() {
+    for (i, bi) in b.iter_mut().enumerate() {
         loop {
             print!("b[{}] = ", i + 1);
-            try!(io::stdout().flush());
+            io::stdout().flush()?;
             buffer.clear();
-            try!(io::stdin().read_line(&mut buffer));
+            io::stdin().read_line(&mut buffer)?;
             match buffer.trim().parse() {
                 Ok(amount) => {
-                    b[i] = amount;
+                    *bi = amount;
                     break;
                 }
                 Err(_) => println!("Ошибка: количество груза должно быть целым неотрицательным числом"),
@@ -124,16 +237,16 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
 
     println!("Введите стоимости перевозок");
     c = vec![vec![0;b.len()];a.len()];
-    for i in 0..a.len() {
-        for j in 0..b.len() {
+    for (i, ci) in c.iter_mut().enumerate() {
+        for (j, cij) in ci.iter_mut().enumerate() {
             loop {
                 print!("c[{}][{}] = ", i + 1, j + 1);
-                try!(io::stdout().flush());
+                io::stdout().flush()?;
                 buffer.clear();
-                try!(io::stdin().read_line(&mut buffer));
+                io::stdin().read_line(&mut buffer)?;
                 match buffer.trim().parse() {
                     Ok(cost) => {
-                        c[i][j] = cost;
+                        *cij = cost;
                         break;
                     }
                     Err(_) => println!("Ошибка: стоимость должна быть целым неотрицательным числом"),
@@ -145,16 +258,172 @@ fn console_input() -> Result<(Vec<u64>, Vec<u64>, Vec<Vec<u64>>), io::Error> {
     Ok((a, b, c))
 }
 
+// The parallel rule shares the costs between threads, so it isn't a Pricing
+enum Rule {
+    Serial(Box<dyn Pricing>),
+    Parallel(Parallel),
+}
+
+// The rule of choosing the entering cell by its name
+fn pricing(name: &str) -> Option<Rule> {
+    match name {
+        "dantzig" => Some(Rule::Serial(Box::new(Dantzig))),
+        "first" => Some(Rule::Serial(Box::new(FirstNegative::default()))),
+        "candidates" => Some(Rule::Serial(Box::new(CandidateList::new(10)))),
+        "block" => Some(Rule::Serial(Box::new(Block::new(64)))),
+        "steepest" => Some(Rule::Serial(Box::new(SteepestEdge::new(10)))),
+        "parallel" => Some(Rule::Parallel(Parallel::new(std::thread::available_parallelism().map_or(1, |n| n.get())))),
+        _ => None,
+    }
+}
+
+// The method of solving the problem by its name
+fn solver(name: &str) -> Option<Box<dyn Solver>> {
+    match name {
+        "potential" => Some(Box::new(PotentialMethod)),
+        "ssp" => Some(Box::new(SuccessiveShortestPath)),
+        "scaling" => Some(Box::new(CostScaling)),
+        _ => None,
+    }
+}
+
+// Solve the problem, with the given pricing rule return the number of iterations and the time.
+// The pricing rule is used by the potential method only.
+fn solve(t: &mut Transportation, rule: Option<&str>, method: Option<&str>) -> Result<Option<(usize, Duration)>, Error> {
+    if let Some(s) = method.filter(|&name| name != "potential").and_then(solver) {
+        t.solve_with(&*s)?;
+        return Ok(None);
+    }
+    match rule.and_then(pricing) {
+        Some(Rule::Serial(mut p)) => Ok(Some(t.potential_method_with(&mut *p))),
+        Some(Rule::Parallel(mut p)) => Ok(Some(t.potential_method_parallel(&mut p))),
+        None => {
+            t.potential_method();
+            Ok(None)
+        }
+    }
+}
+
+// Solve the problem, the options override the ones given in the file.
+// Return the statistics of the pricing rule or the error if an option is unknown or the solver fails.
+fn run(input: &mut Input, rule: Option<&str>, method: Option<&str>) -> Result<Option<(usize, Duration)>, String> {
+    let rule = rule.map(str::to_string).or_else(|| input.rule.clone());
+    let method = method.map(str::to_string).or_else(|| input.method.clone());
+    if let Some(name) = rule.as_deref().filter(|&name| pricing(name).is_none()) {
+        return Err(format!("неизвестное правило выбора клетки {}", name));
+    }
+    if let Some(name) = method.as_deref().filter(|&name| solver(name).is_none()) {
+        return Err(format!("неизвестный метод решения {}", name));
+    }
+    solve(&mut input.t, rule.as_deref(), method.as_deref()).map_err(|err| err.to_string())
+}
+
+// Compare the cost of the plan with the simplex method, the error tells if they differ
+fn check_with_lp(t: &Transportation) -> Result<String, String> {
+    match t.check_with_lp() {
+        Ok((z, true)) => Ok(format!("Симплекс-метод: {}, стоимости совпадают", z)),
+        Ok((z, false)) => Err(format!("симплекс-метод нашёл стоимость {}", z)),
+        Err(err) => Err(format!("симплекс-метод не решил задачу: {}", err)),
+    }
+}
+
+// Solve and print the problem as text or CSV, the errors go to stderr.
+// Return false if the problem can't be solved or the check with the simplex method fails.
+fn print(input: &mut Input, rule: Option<&str>, method: Option<&str>, output: Output, check: bool) -> bool {
+    let stats = match run(input, rule, method) {
+        Ok(stats) => stats,
+        Err(err) => {
+            eprintln!("Ошибка: {}", err);
+            return false;
+        }
+    };
+    if output == Output::Csv {
+        print!("{}", input.t.to_csv(&input.suppliers, &input.customers));
+    } else {
+        input.t.printstd();
+        if let Some((iterations, time)) = stats {
+            println!("Итераций: {}, время: {:?}", iterations, time);
+        }
+    }
+    if !check {
+        return true;
+    }
+    match check_with_lp(&input.t) {
+        Ok(message) => {
+            if output == Output::Text {
+                println!("{}", message);
+            }
+            true
+        }
+        Err(err) => {
+            eprintln!("Ошибка: {}", err);
+            false
+        }
+    }
+}
+
+// Solve the problem read from the file, return the solution or the error as JSON
+#[cfg(feature = "json")]
+fn solution(input: Result<Input, Box<dyn std::error::Error>>, rule: Option<&str>, method: Option<&str>, check: bool) -> Result<serde_json::Value, String> {
+    let mut input = input.map_err(|err| err.to_string())?;
+    let stats = run(&mut input, rule, method)?;
+    if check {
+        check_with_lp(&input.t)?;
+    }
+    let solution = JsonSolution::new(&input.t, stats.map(|s| s.0), &input.suppliers, &input.customers);
+    Ok(serde_json::to_value(solution).unwrap())
+}
+
+// The reader of the format given by the extension of the file, the matrix of costs by default
+fn reader_of(file: &Path) -> Reader {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("lp") => lp_input,
+        Some("mps") => mps_input,
+        Some("csv") => csv_input,
+        #[cfg(feature = "json")]
+        Some("json") => json_input,
+        _ => file_input,
+    }
+}
+
+// Write the problem as an LP or MPS file, the format is chosen by the extension of the output file
+fn convert(input: Reader, from: &PathBuf, to: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let p = input(from)?;
+    let text = match to.extension().and_then(|e| e.to_str()) {
+        Some("lp") => p.t.to_lp(&p.suppliers, &p.customers),
+        Some("mps") => p.t.to_mps(&p.suppliers, &p.customers),
+        _ => return Err(From::from("неизвестный формат, ожидается файл .lp или .mps")),
+    };
+    File::create(to)?.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "json"))]
+const INPUT_FORMATS: &str = "format of the input files: matrix (default), locations, lp, mps or csv";
+#[cfg(feature = "json")]
+const INPUT_FORMATS: &str = "format of the input files: matrix (default), locations, lp, mps, csv or json";
+#[cfg(not(feature = "json"))]
+const OUTPUT_FORMATS: &str = "format of the solutions: text (default) or csv";
+#[cfg(feature = "json")]
+const OUTPUT_FORMATS: &str = "format of the solutions: text (default), csv or json";
+
 fn print_usage(opts: &Options, reason: &str) {
-    let reason = format!("{}\nusage: {} [options] <file>...",
+    let program = std::env::args_os().next().unwrap().to_string_lossy().into_owned();
+    let reason = format!("{}\nusage: {} [options] <file>...\n       {} [options] convert <input> <output.lp|output.mps>",
                          reason,
-                         std::env::args_os().next().unwrap().to_string_lossy());
+                         program,
+                         program);
     println!("{}", opts.usage(&reason));
 }
 
 fn main() {
     let mut opts = Options::new();
     opts.optflag("h", "help", "print this help menu");
+    opts.optopt("f", "input-format", INPUT_FORMATS, "FORMAT");
+    opts.optopt("o", "output-format", OUTPUT_FORMATS, "FORMAT");
+    opts.optopt("p", "pricing", "rule of choosing the entering cell: dantzig, first, candidates, block, steepest or parallel", "RULE");
+    opts.optflag("", "check-with-lp", "compare the cost with the simplex method, exit with 1 if they differ");
+    opts.optopt("s", "solver", "method of solving: potential (default), ssp or scaling", "METHOD");
 
     let matches = match opts.parse(std::env::args().skip(1)) {
         Ok(m) => m,
@@ -169,50 +438,125 @@ fn main() {
         std::process::exit(0);
     }
 
+    let input: Reader = match matches.opt_str("f").as_deref() {
+        None | Some("matrix") => file_input,
+        Some("locations") => locations_input,
+        Some("lp") => lp_input,
+        Some("mps") => mps_input,
+        Some("csv") => csv_input,
+        #[cfg(feature = "json")]
+        Some("json") => json_input,
+        Some(format) => {
+            print_usage(&opts, &format!("Unknown input format: {}", format));
+            std::process::exit(1);
+        }
+    };
+
+    let output = match matches.opt_str("o").as_deref() {
+        None | Some("text") => Output::Text,
+        Some("csv") => Output::Csv,
+        #[cfg(feature = "json")]
+        Some("json") => Output::Json,
+        Some(format) => {
+            print_usage(&opts, &format!("Unknown output format: {}", format));
+            std::process::exit(1);
+        }
+    };
+
+    let rule = matches.opt_str("p");
+    if let Some(ref name) = rule {
+        if pricing(name).is_none() {
+            print_usage(&opts, &format!("Unknown pricing rule: {}", name));
+            std::process::exit(1);
+        }
+    }
+
+    let method = matches.opt_str("s");
+    if let Some(ref name) = method {
+        if solver(name).is_none() {
+            print_usage(&opts, &format!("Unknown solver: {}", name));
+            std::process::exit(1);
+        }
+    }
+
+    if matches.free.first().map(String::as_str) == Some("convert") {
+        if matches.free.len() != 3 {
+            print_usage(&opts, "convert needs the input and the output files");
+            std::process::exit(1);
+        }
+        // Without the option the format of the input is told by its extension
+        let from = PathBuf::from(&matches.free[1]);
+        let input = if matches.opt_present("f") { input } else { reader_of(&from) };
+        if let Err(err) = convert(input, &from, &PathBuf::from(&matches.free[2])) {
+            eprintln!("{}: {}", matches.free[1], err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // The plans of several files would run together into one table
+    if output == Output::Csv && matches.free.len() > 1 {
+        print_usage(&opts, "The csv output format takes a single file");
+        std::process::exit(1);
+    }
+
+    let check = matches.opt_present("check-with-lp");
+    let mut failed = false;
+
     if matches.free.is_empty() {
         let (a, b, c) = match console_input() {
             Ok((a, b, c)) => (a, b, c),
-            Err(err) => panic!(err.to_string()),
+            Err(err) => panic!("{}", err),
         };
-        match Transportation::new(a, b, c) {
-            Ok(mut t) => {
-                t.potential_method();
-                t.printstd();
+        #[cfg(feature = "json")]
+        {
+            if output == Output::Json {
+                match solution(problem(Transportation::new(a, b, c)), rule.as_deref(), method.as_deref(), check) {
+                    Ok(s) => println!("{}", serde_json::to_string_pretty(&s).unwrap()),
+                    Err(err) => {
+                        eprintln!("Ошибка: {}", err);
+                        std::process::exit(1);
+                    }
+                }
+                return;
             }
-            Err(err) => panic!(err.to_string()),
+        }
+        match problem(Transportation::new(a, b, c)) {
+            Ok(mut p) => failed |= !print(&mut p, rule.as_deref(), method.as_deref(), output, check),
+            Err(err) => panic!("{}", err),
         };
     } else {
-        for file in matches.free.iter().map(PathBuf::from) {
-            let (a, b, c) = match file_input(&file) {
-                Ok((a, b, c)) => (a, b, c),
-                Err(err) => {
-                    println!("{:?}:{}", file, err.to_string());
-                    continue;
+        // One object with the solutions or the errors keyed by the names of the files
+        #[cfg(feature = "json")]
+        {
+            if output == Output::Json {
+                let mut solutions = serde_json::Map::new();
+                for file in &matches.free {
+                    let s = solution(input(&PathBuf::from(file)), rule.as_deref(), method.as_deref(), check);
+                    failed |= s.is_err();
+                    solutions.insert(file.clone(), s.unwrap_or_else(|err| serde_json::json!({ "error": err })));
                 }
-            };
-            match Transportation::new(a, b, c) {
-                Ok(mut t) => {
-                    println!("{:?}", file);
-                    t.potential_method();
-                    t.printstd();
+                println!("{}", serde_json::to_string_pretty(&solutions).unwrap());
+                std::process::exit(if failed { 1 } else { 0 });
+            }
+        }
+        for file in matches.free.iter().map(PathBuf::from) {
+            match input(&file) {
+                Ok(mut p) => {
+                    if output == Output::Text {
+                        println!("{:?}", file);
+                    }
+                    failed |= !print(&mut p, rule.as_deref(), method.as_deref(), output, check);
                 }
                 Err(err) => {
-                    match err {
-                        Error::NumOfSupOrCust => {
-                            println!("{:?}: количество поставщиков и потребителей не должно быть меньше 0",
-                                     file)
-                        }
-                        Error::NumOfRows => {
-                            println!("{:?}: количество поставщиков не равно количеству строк в матрице стоимостей",
-                                     file)
-                        }
-                        Error::NumOfCols => {
-                            println!("{:?}: количество потребителей не равно количеству столбцов в матрице стоимостей",
-                                     file)
-                        }
-                    }
+                    eprintln!("{:?}: {}", file, err);
+                    failed = true;
                 }
             }
         }
     }
+
+    if failed {
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,227 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+use prettytable::Table;
+
+use super::{plan_table, Transportation};
+use super::simplex::{self, Relation};
+use super::tariff::capacitated;
+use super::Error::{self, *};
+
+// The number of iterations of the subgradient method
+const ITERATIONS: usize = 50;
+
+// Several products share the capacity of the routes.
+// Every product has its own supply, demand and costs.
+pub struct MultiCommodity {
+    supply: Vec<Vec<u64>>,
+    demand: Vec<Vec<u64>>,
+    cost: Vec<Vec<Vec<u64>>>,
+    capacity: Vec<Vec<u64>>,
+    plans: Vec<Vec<Vec<u64>>>,
+    bound: i64,
+}
+
+impl MultiCommodity {
+    // Solve the transportation problem of the product with the given costs, the dummies are dropped
+    fn solve_product(&self, p: usize, c: Vec<Vec<u64>>) -> (Vec<Vec<u64>>, u64) {
+        let mut t = Transportation::new(self.supply[p].clone(), self.demand[p].clone(), c).unwrap();
+        t.potential_method();
+        (t.original_plan(), t.total_cost())
+    }
+    // The costs of the product increased by the prices of the routes
+    fn priced(&self, p: usize, price: &[Vec<u64>]) -> Vec<Vec<u64>> {
+        self.cost[p]
+            .iter()
+            .zip(price.iter())
+            .map(|(c, l)| c.iter().zip(l.iter()).map(|(c, l)| c + l).collect())
+            .collect()
+    }
+    // Route the products one by one through the capacity left by the previous ones,
+    // starting with the product `first`
+    fn sequential(&self, first: usize, price: &[Vec<u64>]) -> Result<Option<Vec<Vec<Vec<u64>>>>, Error> {
+        let mut left = self.capacity.clone();
+        let mut plans = vec![Vec::new(); self.cost.len()];
+        for k in 0..self.cost.len() {
+            let p = (first + k) % self.cost.len();
+            let plan = match capacitated(&self.supply[p], &self.demand[p], &self.priced(p, price), &left)? {
+                Some(plan) => plan,
+                None => return Ok(None),
+            };
+            for (l, x) in left.iter_mut().zip(plan.iter()) {
+                for (l, x) in l.iter_mut().zip(x.iter()) {
+                    *l -= x;
+                }
+            }
+            plans[p] = plan;
+        }
+        Ok(Some(plans))
+    }
+
+    fn product_cost(&self, p: usize, plan: &[Vec<u64>]) -> u64 {
+        let mut z = 0;
+        for (x, c) in plan.iter().zip(self.cost[p].iter()) {
+            z += x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>();
+        }
+        z
+    }
+
+    fn cost_of(&self, plans: &[Vec<Vec<u64>>]) -> u64 {
+        plans.iter().enumerate().map(|(p, plan)| self.product_cost(p, plan)).sum()
+    }
+    // Lagrangian relaxation of the shared capacities. Every route gets a price that is added
+    // to the costs of all products, so the products are solved independently. The prices
+    // follow the overload of the routes (subgradient method) and give a lower bound of the cost.
+    // At every step the products are also routed one after another at the current prices
+    // to get a feasible plan.
+    pub fn solve(&mut self) -> Result<(), Error> {
+        let rows = self.capacity.len();
+        let cols = self.capacity[0].len();
+        let max = self.cost.iter().flat_map(|c| c.iter()).flat_map(|c| c.iter()).cloned().max().unwrap_or(0);
+        let mut price = vec![vec![0; cols]; rows];
+        let mut best: Option<(Vec<Vec<Vec<u64>>>, u64)> = None;
+        self.bound = 0;
+
+        for k in 0..ITERATIONS {
+            let mut load = vec![vec![0; cols]; rows];
+            let mut value = 0;
+            let mut plans = Vec::new();
+            for p in 0..self.cost.len() {
+                let (plan, z) = self.solve_product(p, self.priced(p, &price));
+                for (l, x) in load.iter_mut().zip(plan.iter()) {
+                    for (l, x) in l.iter_mut().zip(x.iter()) {
+                        *l += x;
+                    }
+                }
+                value += z as i64;
+                plans.push(plan);
+            }
+            for (l, u) in price.iter().zip(self.capacity.iter()) {
+                for (l, u) in l.iter().zip(u.iter()) {
+                    value -= (l * u) as i64;
+                }
+            }
+            self.bound = std::cmp::max(self.bound, value);
+
+            let overloaded = load.iter().zip(self.capacity.iter()).any(|(l, u)| l.iter().zip(u.iter()).any(|(l, u)| l > u));
+            let candidate = if overloaded { self.sequential(k % self.cost.len(), &price)? } else { Some(plans) };
+            if let Some(plans) = candidate {
+                let z = self.cost_of(&plans);
+                if best.as_ref().is_none_or(|b| z < b.1) {
+                    best = Some((plans, z));
+                }
+            }
+            if let Some((_, z)) = best {
+                if z as i64 <= self.bound {
+                    break;
+                }
+            }
+
+            let step = std::cmp::max(1, max / (k as u64 + 1)) as i64;
+            for i in 0..rows {
+                for j in 0..cols {
+                    let g = load[i][j] as i64 - self.capacity[i][j] as i64;
+                    price[i][j] = std::cmp::max(0, price[i][j] as i64 + step * g.signum()) as u64;
+                }
+            }
+        }
+
+        match best {
+            Some((plans, _)) => {
+                self.plans = plans;
+                Ok(())
+            }
+            None => {
+                self.feasible()?;
+                Err(NotConverged)
+            }
+        }
+    }
+    // The heuristic may miss a feasible plan, so the shared capacities are checked exactly
+    // with the linear program without costs. Return Error::Infeasible if they can't be kept.
+    // As in the transportation problem, the smaller of the total supply and demand is sent in full.
+    fn feasible(&self) -> Result<(), Error> {
+        let rows = self.capacity.len();
+        let cols = self.capacity[0].len();
+        let n = rows * cols;
+        let products = self.cost.len();
+        let mut constraints = Vec::new();
+        for (p, (a, b)) in self.supply.iter().zip(self.demand.iter()).enumerate() {
+            let supply: u64 = a.iter().sum();
+            let demand: u64 = b.iter().sum();
+            let (rows_relation, cols_relation) = match supply.cmp(&demand) {
+                std::cmp::Ordering::Less => (Relation::Equal, Relation::LessOrEqual),
+                std::cmp::Ordering::Equal => (Relation::Equal, Relation::Equal),
+                std::cmp::Ordering::Greater => (Relation::LessOrEqual, Relation::Equal),
+            };
+            for (i, &a) in a.iter().enumerate() {
+                let coeffs = (0..n * products).map(|k| if k / n == p && k % n / cols == i { 1.0 } else { 0.0 }).collect();
+                constraints.push((coeffs, rows_relation, a as f64));
+            }
+            for (j, &b) in b.iter().enumerate() {
+                let coeffs = (0..n * products).map(|k| if k / n == p && k % cols == j { 1.0 } else { 0.0 }).collect();
+                constraints.push((coeffs, cols_relation, b as f64));
+            }
+        }
+        for (k, &u) in self.capacity.iter().flat_map(|row| row.iter()).enumerate() {
+            let coeffs = (0..n * products).map(|l| if l % n == k { 1.0 } else { 0.0 }).collect();
+            constraints.push((coeffs, Relation::LessOrEqual, u as f64));
+        }
+        simplex::minimize(&vec![0.0; n * products], &constraints).map(|_| ())
+    }
+    // The plan of the product
+    pub fn plan(&self, p: usize) -> &[Vec<u64>] {
+        &self.plans[p]
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.cost_of(&self.plans)
+    }
+    // The lower bound of the cost given by the Lagrangian relaxation
+    pub fn lower_bound(&self) -> u64 {
+        self.bound as u64
+    }
+
+    pub fn printstd(&self) {
+        for (p, plan) in self.plans.iter().enumerate() {
+            println!("Продукт {}", p + 1);
+            let supply: Vec<String> = self.supply[p].iter().map(u64::to_string).collect();
+            let demand: Vec<String> = self.demand[p].iter().map(u64::to_string).collect();
+            let mut table = plan_table(plan, &supply, &demand);
+            table.add_row(Row::new(vec![Cell::new("Стоимость"), Cell::new(&format!("{}", self.product_cost(p, plan)))]));
+            table.printstd();
+        }
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Нижняя оценка"), Cell::new(&format!("{}", self.lower_bound()))]));
+        table.printstd();
+    }
+    // a[p], b[p] and c[p] are the supply, the demand and the costs of the product p,
+    // u is the capacity of the routes shared by all products
+    pub fn new(a: Vec<Vec<u64>>, b: Vec<Vec<u64>>, c: Vec<Vec<Vec<u64>>>, u: Vec<Vec<u64>>) -> Result<MultiCommodity, Error> {
+        if a.is_empty() || a.len() != b.len() || a.len() != c.len() {
+            return Err(NumOfProducts);
+        }
+        if a[0].is_empty() || b[0].is_empty() || a.iter().any(|a| a.len() != u.len()) || b.iter().any(|v| v.len() != b[0].len()) {
+            return Err(NumOfSupOrCust);
+        }
+        for c in c.iter().chain(Some(&u)) {
+            if a[0].len() != c.len() {
+                return Err(NumOfRows);
+            }
+            for i in c {
+                if b[0].len() != i.len() {
+                    return Err(NumOfCols);
+                }
+            }
+        }
+
+        Ok(MultiCommodity {
+            supply: a,
+            demand: b,
+            cost: c,
+            capacity: u,
+            plans: Vec::new(),
+            bound: 0,
+        })
+    }
+}
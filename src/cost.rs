@@ -0,0 +1,19 @@
+use super::Matrix;
+
+// The costs of sending a unit. They are queried cell by cell,
+// so a large problem may compute them on demand instead of keeping a matrix.
+pub trait CostSource {
+    // The number of suppliers and customers
+    fn shape(&self) -> (usize, usize);
+    fn cost(&self, i: usize, j: usize) -> u64;
+}
+
+impl CostSource for Matrix<u64> {
+    fn shape(&self) -> (usize, usize) {
+        (self.rows(), self.cols())
+    }
+
+    fn cost(&self, i: usize, j: usize) -> u64 {
+        self[i][j]
+    }
+}
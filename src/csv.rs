@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use super::{label, CostSource, Imported, Transportation};
+use super::Error::{self, *};
+
+// The name of the column with the supply
+const SUPPLY: &str = "supply";
+// The name of the row with the demand
+const DEMAND: &str = "demand";
+
+// The records of the text, a field in double quotes may have commas, line breaks and doubled quotes.
+// The blank lines are skipped.
+fn records(text: &str) -> Result<Vec<Vec<String>>, Error> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err(Csv),
+                }
+            }
+            // Only the spaces may follow the closing quote
+            while let Some(&c) = chars.peek() {
+                if c == ',' || c == '\n' {
+                    break;
+                }
+                if !c.is_whitespace() {
+                    return Err(Csv);
+                }
+                chars.next();
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c == ',' || c == '\n' {
+                    break;
+                }
+                field.push(c);
+                chars.next();
+            }
+            field = field.trim().to_string();
+        }
+        record.push(field);
+        match chars.next() {
+            Some(',') => {}
+            end => {
+                // A blank line is a single empty field
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(record);
+                }
+                record = Vec::new();
+                if end.is_none() {
+                    return Ok(records);
+                }
+            }
+        }
+    }
+}
+
+// The field in double quotes if it has a comma, a quote, a line break or spaces around it
+fn quote(field: &str) -> String {
+    if field.contains(',') || field.contains('"') || field.contains('\n') || field.trim() != field {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn number(field: &str) -> Result<u64, Error> {
+    field.parse().map_err(|_| Csv)
+}
+
+// Read a problem from a table: the header holds the names of the customers and the supply column,
+// every row but the last is a supplier with its name, costs and supply, the last row holds the demand.
+// The first field of the header and the supply field of the last row are ignored.
+//
+// ,Kaluga,Ryazan,supply
+// Moscow,1,2,30
+// Tula,3,1,20
+// demand,25,25,
+pub fn parse_csv(text: &str) -> Result<Imported, Error> {
+    let mut rows = records(text)?;
+    if rows.len() < 2 {
+        return Err(Csv);
+    }
+    let header = rows.remove(0);
+    let demand_row = rows.pop().unwrap();
+    let supply: Vec<usize> = (1..header.len()).filter(|&k| header[k].eq_ignore_ascii_case(SUPPLY)).collect();
+    if supply.len() != 1 {
+        return Err(Csv);
+    }
+    let supply_col = supply[0];
+    // The columns of the costs
+    let cols: Vec<usize> = (1..header.len()).filter(|&k| k != supply_col).collect();
+    if rows.iter().chain(Some(&demand_row)).any(|row| row.len() != header.len() && row.len() != header.len() - 1) {
+        return Err(NumOfCols);
+    }
+
+    let customers = cols.iter().map(|&k| header[k].clone()).collect();
+    let suppliers = rows.iter().map(|row| row[0].clone()).collect();
+    let mut a = Vec::new();
+    let mut c = Vec::new();
+    for row in &rows {
+        a.push(number(row.get(supply_col).ok_or(Csv)?)?);
+        c.push(cols.iter().map(|&k| number(row.get(k).ok_or(Csv)?)).collect::<Result<Vec<u64>, Error>>()?);
+    }
+    let b = cols.iter().map(|&k| number(demand_row.get(k).ok_or(Csv)?)).collect::<Result<Vec<u64>, Error>>()?;
+    Ok((Transportation::new(a, b, c)?, suppliers, customers))
+}
+
+impl<C: CostSource> Transportation<C> {
+    // The plan in the shape of parse_csv: the amounts of the original suppliers and customers,
+    // the supply column holds what every supplier ships and the demand row what every customer gets.
+    // The missing labels are A1, B1 and so on.
+    pub fn to_csv(&self, suppliers: &[String], customers: &[String]) -> String {
+        let plan = self.original_plan();
+        let cols = plan.first().map_or(0, |row| row.len());
+        let mut out = String::new();
+        let header: Vec<String> = (0..cols).map(|j| quote(&label(customers, j, "B"))).collect();
+        writeln!(out, ",{},{}", header.join(","), SUPPLY).unwrap();
+        for (i, row) in plan.iter().enumerate() {
+            let amounts: Vec<String> = row.iter().map(u64::to_string).collect();
+            writeln!(out, "{},{},{}", quote(&label(suppliers, i, "A")), amounts.join(","), row.iter().sum::<u64>()).unwrap();
+        }
+        let received: Vec<String> = (0..cols).map(|j| plan.iter().map(|row| row[j]).sum::<u64>().to_string()).collect();
+        writeln!(out, "{},{},", DEMAND, received.join(",")).unwrap();
+        out
+    }
+}
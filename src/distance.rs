@@ -0,0 +1,83 @@
+use super::{CostSource, Transportation};
+use super::Error;
+
+// The mean radius of the Earth in kilometres
+const EARTH_RADIUS: f64 = 6371.0;
+
+// The distance between two points
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Metric {
+    Euclidean,
+    Manhattan,
+    // The great-circle distance in kilometres, the coordinates are the latitude and the longitude in degrees
+    Haversine,
+}
+
+impl Metric {
+    pub fn distance(&self, p: (f64, f64), q: (f64, f64)) -> f64 {
+        match *self {
+            Metric::Euclidean => (p.0 - q.0).hypot(p.1 - q.1),
+            Metric::Manhattan => (p.0 - q.0).abs() + (p.1 - q.1).abs(),
+            Metric::Haversine => {
+                let (lat1, lat2) = (p.0.to_radians(), q.0.to_radians());
+                let dlat = lat2 - lat1;
+                let dlon = (q.1 - p.1).to_radians();
+                let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+            }
+        }
+    }
+}
+
+// A supplier or a customer: the coordinates, the amount of goods and the cost of handling a unit
+pub type Location = (f64, f64, u64, u64);
+
+// The cost of a unit is the distance multiplied by the rate and rounded,
+// plus the handling costs at both ends
+fn unit_cost(s: &Location, c: &Location, metric: Metric, rate: f64) -> u64 {
+    (metric.distance((s.0, s.1), (c.0, c.1)) * rate).round() as u64 + s.3 + c.3
+}
+
+pub fn location_costs(suppliers: &[Location], customers: &[Location], metric: Metric, rate: f64) -> Vec<Vec<u64>> {
+    suppliers.iter().map(|s| customers.iter().map(|c| unit_cost(s, c, metric, rate)).collect()).collect()
+}
+
+// The costs computed from the locations when they are needed instead of the whole matrix
+pub struct LocationCosts {
+    suppliers: Vec<Location>,
+    customers: Vec<Location>,
+    metric: Metric,
+    rate: f64,
+}
+
+impl LocationCosts {
+    pub fn new(suppliers: Vec<Location>, customers: Vec<Location>, metric: Metric, rate: f64) -> LocationCosts {
+        LocationCosts {
+            suppliers,
+            customers,
+            metric,
+            rate,
+        }
+    }
+}
+
+impl CostSource for LocationCosts {
+    fn shape(&self) -> (usize, usize) {
+        (self.suppliers.len(), self.customers.len())
+    }
+
+    fn cost(&self, i: usize, j: usize) -> u64 {
+        unit_cost(&self.suppliers[i], &self.customers[j], self.metric, self.rate)
+    }
+}
+
+impl Transportation {
+    // Return Error::Rate if the rate is negative or not a finite number
+    pub fn with_locations(suppliers: Vec<Location>, customers: Vec<Location>, metric: Metric, rate: f64) -> Result<Transportation, Error> {
+        if !rate.is_finite() || rate < 0.0 {
+            return Err(Error::Rate);
+        }
+        let c = location_costs(&suppliers, &customers, metric, rate);
+        Transportation::new(suppliers.iter().map(|s| s.2).collect(), customers.iter().map(|c| c.2).collect(), c)
+    }
+}
@@ -0,0 +1,141 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+use prettytable::Table;
+
+use super::{checked_prohibitive_cost, plan_table, Transportation};
+use super::Error::{self, *};
+
+// Plants send the goods to the customers through the warehouses,
+// every warehouse can pass a limited amount of goods.
+//
+// It is reduced to a transportation problem: the rows are the plants and the warehouses,
+// the columns are the warehouses and the customers. The row of a warehouse has its capacity
+// as the supply, the column has it as the demand. What the warehouse doesn't pass to the
+// customers goes to its own column, so its column gets from the plants exactly what it passes.
+pub struct TwoStage {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    capacity: Vec<u64>,
+    first: Vec<Vec<u64>>,
+    second: Vec<Vec<u64>>,
+    inner: Transportation,
+}
+
+impl TwoStage {
+    // The shipments from the plants to the warehouses
+    pub fn first_stage(&self) -> Vec<Vec<u64>> {
+        let plan = self.inner.plan();
+        plan[..self.supply.len()].iter().map(|row| row[..self.capacity.len()].to_vec()).collect()
+    }
+    // The shipments from the warehouses to the customers
+    pub fn second_stage(&self) -> Vec<Vec<u64>> {
+        let plan = self.inner.plan();
+        let rows = self.supply.len();
+        let cols = self.capacity.len();
+        plan[rows..rows + cols].iter().map(|row| row[cols..cols + self.demand.len()].to_vec()).collect()
+    }
+    // The amount passed by every warehouse
+    pub fn throughput(&self) -> Vec<u64> {
+        self.second_stage().iter().map(|row| row.iter().sum()).collect()
+    }
+
+    fn cost_of(plan: &[Vec<u64>], cost: &[Vec<u64>]) -> u64 {
+        plan.iter().zip(cost.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum()
+    }
+
+    pub fn first_stage_cost(&self) -> u64 {
+        TwoStage::cost_of(&self.first_stage(), &self.first)
+    }
+
+    pub fn second_stage_cost(&self) -> u64 {
+        TwoStage::cost_of(&self.second_stage(), &self.second)
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.first_stage_cost() + self.second_stage_cost()
+    }
+    // Return Error::Infeasible if the capacities of the warehouses can't pass the goods
+    pub fn solve(&mut self) -> Result<(), Error> {
+        self.inner.potential_method();
+        // Only the forbidden routes have the prohibitive cost
+        let m = self.inner.cost.data.iter().cloned().max().unwrap_or(0);
+        let used = self.inner.trans.cells().any(|(i, j, x)| x > 0 && self.inner.cost[i][j] == m);
+        if used {
+            return Err(Infeasible);
+        }
+        Ok(())
+    }
+
+    pub fn printstd(&self) {
+        let throughput: Vec<String> = self.throughput().iter().zip(self.capacity.iter()).map(|(x, w)| format!("{} ({})", x, w)).collect();
+
+        println!("Этап 1: заводы - склады");
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.first_stage(), &supply, &throughput);
+        table.add_row(Row::new(vec![Cell::new("Стоимость"), Cell::new(&format!("{}", self.first_stage_cost()))]));
+        table.printstd();
+
+        println!("Этап 2: склады - потребители");
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.second_stage(), &throughput, &demand);
+        table.add_row(Row::new(vec![Cell::new("Стоимость"), Cell::new(&format!("{}", self.second_stage_cost()))]));
+        table.printstd();
+
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.printstd();
+    }
+    // a is the supply of the plants, b is the demand of the customers, w is the capacity of the warehouses,
+    // c1 is the cost from the plants to the warehouses, c2 is the cost from the warehouses to the customers
+    pub fn new(a: Vec<u64>, b: Vec<u64>, w: Vec<u64>, c1: Vec<Vec<u64>>, c2: Vec<Vec<u64>>) -> Result<TwoStage, Error> {
+        if a.is_empty() || b.is_empty() || w.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c1.len() || w.len() != c2.len() {
+            return Err(NumOfRows);
+        }
+        if c1.iter().any(|i| i.len() != w.len()) || c2.iter().any(|i| i.len() != b.len()) {
+            return Err(NumOfCols);
+        }
+
+        let rows = a.len();
+        let cols = w.len();
+        let all: Vec<Vec<u64>> = c1.iter().chain(c2.iter()).cloned().collect();
+        let m = checked_prohibitive_cost(&all, 2 * a.iter().sum::<u64>())?;
+        let mut c = Vec::new();
+        for row in &c1 {
+            let mut row = row.clone();
+            row.extend(vec![m; b.len()]);
+            c.push(row);
+        }
+        for (k, row) in c2.iter().enumerate() {
+            let mut row2: Vec<u64> = (0..cols).map(|l| if l == k { 0 } else { m }).collect();
+            row2.extend(row.iter().cloned());
+            c.push(row2);
+        }
+        let mut supply = a.clone();
+        supply.extend(w.iter().cloned());
+        let mut demand = w.clone();
+        demand.extend(b.iter().cloned());
+
+        let mut inner = Transportation::new(supply, demand, c)?;
+        // The dummy must not pass through the warehouses
+        for k in 0..cols {
+            if inner.cost.cols() > cols + b.len() {
+                inner.cost[rows + k][cols + b.len()] = m;
+            }
+            if inner.cost.rows() > rows + cols {
+                inner.cost[rows + cols][k] = m;
+            }
+        }
+
+        Ok(TwoStage {
+            supply: a,
+            demand: b,
+            capacity: w,
+            first: c1,
+            second: c2,
+            inner,
+        })
+    }
+}
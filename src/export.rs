@@ -0,0 +1,185 @@
+use std::fmt::Write;
+
+use super::{label, CostSource, Relation, Transportation};
+
+// The number of terms in a line of an LP file
+const TERMS_PER_LINE: usize = 8;
+
+// Only letters, digits and underscores are kept, so the name is valid in both formats
+fn sanitize(name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+// Repeated names get the suffixes _2, _3 and so on
+fn dedup(names: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for name in names {
+        let mut unique = name.clone();
+        let mut k = 1;
+        while result.contains(&unique) {
+            k += 1;
+            unique = format!("{}_{}", name, k);
+        }
+        result.push(unique);
+    }
+    result
+}
+
+// The sum of the terms split into lines
+fn sum(terms: &[String]) -> String {
+    let lines: Vec<String> = terms.chunks(TERMS_PER_LINE).map(|chunk| chunk.join(" + ")).collect();
+    lines.join("\n   + ")
+}
+
+// The problem as the names of the rows and the columns, the costs, the rows of the amounts
+// and the forbidden cells
+struct Model {
+    rows: Vec<String>,
+    cols: Vec<String>,
+    cost: Vec<Vec<u64>>,
+    supply: Vec<(Relation, u64)>,
+    demand: Vec<(Relation, u64)>,
+    forbidden: Vec<(usize, usize)>,
+}
+
+impl Model {
+    fn var(&self, i: usize, j: usize) -> String {
+        format!("x_{}_{}", self.rows[i], self.cols[j])
+    }
+}
+
+fn lp_relation(r: Relation) -> &'static str {
+    match r {
+        Relation::LessOrEqual => "<=",
+        Relation::GreaterOrEqual => ">=",
+        Relation::Equal => "=",
+    }
+}
+
+fn mps_relation(r: Relation) -> &'static str {
+    match r {
+        Relation::LessOrEqual => "L",
+        Relation::GreaterOrEqual => "G",
+        Relation::Equal => "E",
+    }
+}
+
+impl<C: CostSource> Transportation<C> {
+    fn model(&self, suppliers: &[String], customers: &[String]) -> Model {
+        // The ranges that are single relations are written as the original problem
+        if let Some(ref ranges) = self.ranges {
+            if let Some((supply, demand)) = ranges.relations() {
+                let names = |size: usize, labels: &[String], prefix: &str| dedup((0..size).map(|k| sanitize(&label(labels, k, prefix))).collect());
+                return Model {
+                    rows: names(supply.len(), suppliers, "A"),
+                    cols: names(demand.len(), customers, "B"),
+                    cost: ranges.costs().to_vec(),
+                    supply,
+                    demand,
+                    forbidden: self.forbidden.clone(),
+                };
+            }
+        }
+
+        let (rows, cols) = (self.supply.len(), self.demand.len());
+        let (row_names, col_names) = self.names(suppliers, customers);
+        let mut forbidden = if let Some(ref ranges) = self.ranges {
+            ranges.forbidden(self)
+        } else if let Some(ref tariffs) = self.tariffs {
+            tariffs.forbidden(self)
+        } else {
+            Vec::new()
+        };
+        // The routes missing in the imported model stay fixed at zero
+        match self.ranges {
+            Some(ref ranges) => forbidden.extend(ranges.cells(&self.forbidden)),
+            None => forbidden.extend(self.forbidden.iter().cloned()),
+        }
+        forbidden.sort();
+        forbidden.dedup();
+
+        Model {
+            rows: dedup(row_names.iter().map(|n| sanitize(n)).collect()),
+            cols: dedup(col_names.iter().map(|n| sanitize(n)).collect()),
+            cost: (0..rows).map(|i| (0..cols).map(|j| self.cost_of(i, j)).collect()).collect(),
+            supply: self.supply.iter().map(|&a| (Relation::Equal, a)).collect(),
+            demand: self.demand.iter().map(|&b| (Relation::Equal, b)).collect(),
+            forbidden,
+        }
+    }
+    // The balanced problem in the CPLEX LP format. The variable x_<supplier>_<customer> is named
+    // after the labels, A1, B1 and so on if they are missing. The dummies are named dummy and
+    // their cells that the problem forbids are fixed at zero, as well as the missing routes of an imported model.
+    // The problem with ranges is written with <= and >= rows instead when every range is a single relation,
+    // e.g. if it was read from such a model.
+    pub fn to_lp(&self, suppliers: &[String], customers: &[String]) -> String {
+        let m = self.model(suppliers, customers);
+        let mut out = String::new();
+        writeln!(out, "\\ Transportation problem").unwrap();
+        writeln!(out, "Minimize").unwrap();
+        let objective: Vec<String> = (0..m.rows.len())
+                                         .flat_map(|i| (0..m.cols.len()).map(move |j| (i, j)))
+                                         .map(|(i, j)| format!("{} {}", m.cost[i][j], m.var(i, j)))
+                                         .collect();
+        writeln!(out, " cost: {}", sum(&objective)).unwrap();
+        writeln!(out, "Subject To").unwrap();
+        for (i, &(r, a)) in m.supply.iter().enumerate() {
+            let terms: Vec<String> = (0..m.cols.len()).map(|j| m.var(i, j)).collect();
+            writeln!(out, " s_{}: {} {} {}", m.rows[i], sum(&terms), lp_relation(r), a).unwrap();
+        }
+        for (j, &(r, b)) in m.demand.iter().enumerate() {
+            let terms: Vec<String> = (0..m.rows.len()).map(|i| m.var(i, j)).collect();
+            writeln!(out, " d_{}: {} {} {}", m.cols[j], sum(&terms), lp_relation(r), b).unwrap();
+        }
+        if !m.forbidden.is_empty() {
+            writeln!(out, "Bounds").unwrap();
+            for &(i, j) in &m.forbidden {
+                writeln!(out, " {} = 0", m.var(i, j)).unwrap();
+            }
+        }
+        writeln!(out, "End").unwrap();
+        out
+    }
+    // The balanced problem in the free MPS format with the same names as to_lp
+    pub fn to_mps(&self, suppliers: &[String], customers: &[String]) -> String {
+        let m = self.model(suppliers, customers);
+        let mut out = String::new();
+        writeln!(out, "NAME transportation").unwrap();
+        writeln!(out, "ROWS").unwrap();
+        writeln!(out, " N cost").unwrap();
+        for (row, &(r, _)) in m.rows.iter().zip(m.supply.iter()) {
+            writeln!(out, " {} s_{}", mps_relation(r), row).unwrap();
+        }
+        for (col, &(r, _)) in m.cols.iter().zip(m.demand.iter()) {
+            writeln!(out, " {} d_{}", mps_relation(r), col).unwrap();
+        }
+        writeln!(out, "COLUMNS").unwrap();
+        for i in 0..m.rows.len() {
+            for j in 0..m.cols.len() {
+                let var = m.var(i, j);
+                writeln!(out, " {} cost {} s_{} 1", var, m.cost[i][j], m.rows[i]).unwrap();
+                writeln!(out, " {} d_{} 1", var, m.cols[j]).unwrap();
+            }
+        }
+        writeln!(out, "RHS").unwrap();
+        for (row, &(_, a)) in m.rows.iter().zip(m.supply.iter()) {
+            writeln!(out, " RHS s_{} {}", row, a).unwrap();
+        }
+        for (col, &(_, b)) in m.cols.iter().zip(m.demand.iter()) {
+            writeln!(out, " RHS d_{} {}", col, b).unwrap();
+        }
+        if !m.forbidden.is_empty() {
+            writeln!(out, "BOUNDS").unwrap();
+            for &(i, j) in &m.forbidden {
+                writeln!(out, " FX BND {} 0", m.var(i, j)).unwrap();
+            }
+        }
+        writeln!(out, "ENDATA").unwrap();
+        out
+    }
+}
@@ -0,0 +1,239 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{checked_prohibitive_cost, plan_table, Transportation};
+use super::Error::{self, *};
+
+// Linearized costs are kept in integers multiplied by this factor
+const SCALE: u64 = 1000;
+// The limit of iterations of the linearized cost method
+const MAX_ITERATIONS: usize = 100;
+
+// Transportation problem where every used route costs a fixed charge
+// in addition to the cost per unit
+pub struct FixedCharge {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    cost: Vec<Vec<u64>>,
+    fixed: Vec<Vec<u64>>,
+    plan: Vec<Vec<u64>>,
+}
+
+impl FixedCharge {
+    // The largest amount that can be sent by the route
+    fn capacity(&self, i: usize, j: usize) -> u64 {
+        std::cmp::min(self.supply[i], self.demand[j])
+    }
+    // Solve the transportation problem with the given costs and drop the dummies from the plan
+    fn solve(&self, c: Vec<Vec<u64>>) -> Vec<Vec<u64>> {
+        let mut t = Transportation::new(self.supply.clone(), self.demand.clone(), c).unwrap();
+        t.potential_method();
+        t.original_plan()
+    }
+    // Fixed and variable cost of the plan
+    fn costs(&self, plan: &[Vec<u64>]) -> (u64, u64) {
+        let mut fixed = 0;
+        let mut variable = 0;
+        for (i, row) in plan.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                if x > 0 {
+                    fixed += self.fixed[i][j];
+                    variable += x * self.cost[i][j];
+                }
+            }
+        }
+        (fixed, variable)
+    }
+    // The cost per unit with the fixed charge spread over the amount
+    fn linearized(&self, i: usize, j: usize, amount: u64) -> u64 {
+        let amount = std::cmp::max(amount, 1);
+        self.cost[i][j] * SCALE + self.fixed[i][j] * SCALE / amount
+    }
+    // Linearized costs of the plan: the fixed charge of the used routes is spread over
+    // their amount, the unused routes spread it over their capacity
+    fn linearization(&self, plan: &[Vec<u64>]) -> Vec<Vec<u64>> {
+        plan.iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                   .enumerate()
+                   .map(|(j, &x)| self.linearized(i, j, if x > 0 { x } else { self.capacity(i, j) }))
+                   .collect()
+            })
+            .collect()
+    }
+
+    // A cost that closes a route: it exceeds any linearized cost of the whole supply
+    fn prohibitive(&self) -> Result<u64, Error> {
+        let c: Vec<Vec<u64>> = (0..self.supply.len()).map(|i| (0..self.demand.len()).map(|j| self.linearized(i, j, 1)).collect()).collect();
+        checked_prohibitive_cost(&c, self.supply.iter().sum())
+    }
+
+    pub fn fixed_cost(&self) -> u64 {
+        self.costs(&self.plan).0
+    }
+
+    pub fn variable_cost(&self) -> u64 {
+        self.costs(&self.plan).1
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        let (fixed, variable) = self.costs(&self.plan);
+        fixed + variable
+    }
+    // Keep the plan if it is cheaper than the current one
+    fn update(&mut self, plan: Vec<Vec<u64>>) -> bool {
+        let (fixed, variable) = self.costs(&plan);
+        if self.plan.is_empty() || fixed + variable < self.total_cost() {
+            self.plan = plan;
+            return true;
+        }
+        false
+    }
+    // Iterate the linearized costs until the plan stops changing,
+    // then try to close the used routes one by one while it reduces the total cost
+    pub fn heuristic(&mut self) {
+        let empty = vec![vec![0; self.demand.len()]; self.supply.len()];
+        let mut plan = self.solve(self.linearization(&empty));
+        for _ in 0..MAX_ITERATIONS {
+            let next = self.solve(self.linearization(&plan));
+            self.update(plan.clone());
+            if next == plan {
+                break;
+            }
+            plan = next;
+        }
+        self.update(plan);
+
+        // new has checked it
+        let m = self.prohibitive().unwrap();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            let lanes: Vec<(usize, usize)> = self.used_lanes();
+            for (i, j) in lanes {
+                let mut c = self.linearization(&self.plan);
+                c[i][j] = m;
+                let plan = self.solve(c);
+                if plan[i][j] == 0 && self.update(plan) {
+                    improved = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn used_lanes(&self) -> Vec<(usize, usize)> {
+        let mut lanes = Vec::new();
+        for (i, row) in self.plan.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                if x > 0 {
+                    lanes.push((i, j));
+                }
+            }
+        }
+        lanes
+    }
+    // Exact solution for small problems. Every route is either undecided, opened or closed.
+    // The lower bound of a node is given by the transportation problem where the fixed charge
+    // of the undecided routes is spread over their capacity.
+    pub fn branch_and_bound(&mut self) {
+        self.heuristic();
+
+        let rows = self.supply.len();
+        let cols = self.demand.len();
+        let m = self.prohibitive().unwrap();
+
+        let mut stack = vec![vec![None; rows * cols]];
+        while let Some(state) = stack.pop() {
+            let mut c = vec![vec![0; cols]; rows];
+            let mut opened = 0;
+            for i in 0..rows {
+                for j in 0..cols {
+                    c[i][j] = match state[i * cols + j] {
+                        None => self.linearized(i, j, self.capacity(i, j)),
+                        Some(true) => {
+                            opened += self.fixed[i][j];
+                            self.cost[i][j] * SCALE
+                        }
+                        Some(false) => m,
+                    };
+                }
+            }
+            let plan = self.solve(c.clone());
+            let mut bound = 0;
+            let mut closed_used = false;
+            for i in 0..rows {
+                for j in 0..cols {
+                    bound += plan[i][j] * c[i][j];
+                    closed_used |= state[i * cols + j] == Some(false) && plan[i][j] > 0;
+                }
+            }
+            if closed_used || opened + bound.div_ceil(SCALE) >= self.total_cost() {
+                continue;
+            }
+            self.update(plan.clone());
+
+            // Branch on the undecided route whose fixed charge is underestimated the most.
+            // A route used at its capacity is still branched on if its linearized cost was rounded.
+            let mut branch = None;
+            let mut gap = 0;
+            for i in 0..rows {
+                for j in 0..cols {
+                    let x = plan[i][j];
+                    let u = self.capacity(i, j);
+                    if state[i * cols + j].is_none() && x > 0 && (x < u || !(self.fixed[i][j] * SCALE).is_multiple_of(u)) {
+                        let g = self.fixed[i][j] * (u - x) / u;
+                        if branch.is_none() || g > gap {
+                            branch = Some(i * cols + j);
+                            gap = g;
+                        }
+                    }
+                }
+            }
+            if let Some(k) = branch {
+                let mut closed = state.clone();
+                closed[k] = Some(false);
+                stack.push(closed);
+                let mut open = state;
+                open[k] = Some(true);
+                stack.push(open);
+            }
+        }
+    }
+
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan, &supply, &demand);
+        let (fixed, variable) = self.costs(&self.plan);
+        table.add_row(Row::new(vec![Cell::new("Постоянные затраты"), Cell::new(&format!("{}", fixed))]));
+        table.add_row(Row::new(vec![Cell::new("Переменные затраты"), Cell::new(&format!("{}", variable))]));
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", fixed + variable))]));
+        table.printstd();
+    }
+
+    pub fn new(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>, f: Vec<Vec<u64>>) -> Result<FixedCharge, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() || a.len() != f.len() {
+            return Err(NumOfRows);
+        }
+        for i in c.iter().chain(f.iter()) {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        let t = FixedCharge {
+            supply: a,
+            demand: b,
+            cost: c,
+            fixed: f,
+            plan: Vec::new(),
+        };
+        t.prohibitive()?;
+        Ok(t)
+    }
+}
@@ -0,0 +1,91 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::plan_table;
+use super::simplex::{self, Relation};
+use super::Error::{self, *};
+
+// Generalized transportation problem: a unit sent from the supplier i arrives at the customer j
+// as g[i][j] units because of losses (g < 1) or conversion (g > 1). The amounts are no longer
+// integral and the potentials of a basis don't follow from the costs alone, so it is solved
+// as a linear program: the suppliers send at most their supply and every customer receives
+// exactly its demand.
+pub struct Generalized {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    cost: Vec<Vec<u64>>,
+    gain: Vec<Vec<f64>>,
+    plan: Vec<Vec<f64>>,
+}
+
+impl Generalized {
+    // Return Error::Infeasible if the supply can't cover the demand after the losses
+    pub fn solve(&mut self) -> Result<(), Error> {
+        let rows = self.supply.len();
+        let cols = self.demand.len();
+        let c: Vec<f64> = self.cost.iter().flat_map(|row| row.iter().map(|&c| c as f64)).collect();
+        let mut constraints = Vec::new();
+        for (i, &a) in self.supply.iter().enumerate() {
+            let coeffs = (0..rows * cols).map(|k| if k / cols == i { 1.0 } else { 0.0 }).collect();
+            constraints.push((coeffs, Relation::LessOrEqual, a as f64));
+        }
+        for (j, &b) in self.demand.iter().enumerate() {
+            let coeffs = (0..rows * cols).map(|k| if k % cols == j { self.gain[k / cols][j] } else { 0.0 }).collect();
+            constraints.push((coeffs, Relation::Equal, b as f64));
+        }
+        let (x, _) = simplex::minimize(&c, &constraints)?;
+        self.plan = x.chunks(cols).map(|row| row.to_vec()).collect();
+        Ok(())
+    }
+    // The amounts sent from the suppliers
+    pub fn plan(&self) -> &[Vec<f64>] {
+        &self.plan
+    }
+    // The amounts that arrive at the customers
+    pub fn delivered(&self) -> Vec<Vec<f64>> {
+        self.plan.iter().zip(self.gain.iter()).map(|(x, g)| x.iter().zip(g.iter()).map(|(x, g)| x * g).collect()).collect()
+    }
+    // The cost is paid for every unit sent
+    pub fn total_cost(&self) -> f64 {
+        self.plan.iter().zip(self.cost.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, &c)| x * c as f64).sum::<f64>()).sum()
+    }
+
+    pub fn printstd(&self) {
+        let plan: Vec<Vec<String>> = self.plan.iter().map(|row| row.iter().map(|x| format!("{:.2}", x)).collect()).collect();
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&plan, &supply, &demand);
+        let mut row = Row::new(vec![Cell::new("Доставлено")]);
+        let delivered = self.delivered();
+        for j in 0..self.demand.len() {
+            row.add_cell(Cell::new(&format!("{:.2}", delivered.iter().map(|x| x[j]).sum::<f64>())));
+        }
+        table.add_row(row);
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{:.2}", self.total_cost()))]));
+        table.printstd();
+    }
+    // a is the supply, b is the demand, c is the cost of sending a unit
+    // and g is the amount that arrives per unit sent
+    pub fn new(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>, g: Vec<Vec<f64>>) -> Result<Generalized, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() || a.len() != g.len() {
+            return Err(NumOfRows);
+        }
+        if c.iter().any(|i| i.len() != b.len()) || g.iter().any(|i| i.len() != b.len()) {
+            return Err(NumOfCols);
+        }
+        if g.iter().flat_map(|row| row.iter()).any(|&g| !(g > 0.0 && g.is_finite())) {
+            return Err(Gain);
+        }
+
+        Ok(Generalized {
+            supply: a,
+            demand: b,
+            cost: c,
+            gain: g,
+            plan: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,609 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::{checked_prohibitive_cost, Relation, Transportation};
+use super::Error::{self, *};
+
+// The problem and the labels of the suppliers and the customers
+pub type Imported = (Transportation, Vec<String>, Vec<String>);
+
+// A constraint: the name, the terms, the relation and the right-hand side
+type Row = (String, Vec<(usize, f64)>, Relation, f64);
+
+// A linear program as it is read from a file
+struct Model {
+    maximize: bool,
+    vars: Vec<String>,
+    index: HashMap<String, usize>,
+    cost: Vec<f64>,
+    rows: Vec<Row>,
+    // The variables fixed at zero
+    fixed: Vec<bool>,
+}
+
+impl Model {
+    fn new() -> Model {
+        Model {
+            maximize: false,
+            vars: Vec::new(),
+            index: HashMap::new(),
+            cost: Vec::new(),
+            rows: Vec::new(),
+            fixed: Vec::new(),
+        }
+    }
+    // The index of the variable, a new one is added
+    fn var(&mut self, name: &str) -> usize {
+        if let Some(&v) = self.index.get(name) {
+            return v;
+        }
+        self.vars.push(name.to_string());
+        self.cost.push(0.0);
+        self.fixed.push(false);
+        self.index.insert(name.to_string(), self.vars.len() - 1);
+        self.vars.len() - 1
+    }
+    // The routes can't have lower bounds, the only upper bound is zero for a forbidden route
+    fn lower(&mut self, name: &str, value: f64) -> Result<(), Error> {
+        self.var(name);
+        if value != 0.0 {
+            return Err(NotTransportation);
+        }
+        Ok(())
+    }
+
+    fn upper(&mut self, name: &str, value: f64) -> Result<(), Error> {
+        let v = self.var(name);
+        if value == 0.0 {
+            self.fixed[v] = true;
+        } else if value != f64::INFINITY {
+            return Err(NotTransportation);
+        }
+        Ok(())
+    }
+}
+
+// An amount or a cost must be a non-negative integer
+fn amount(x: f64) -> Result<u64, Error> {
+    if x >= 0.0 && x.fract() == 0.0 && x < u64::MAX as f64 {
+        Ok(x as u64)
+    } else {
+        Err(NotTransportation)
+    }
+}
+
+// Recognize the transportation problem: every variable is a route with the coefficient 1 in the row
+// of its supplier and the row of its customer, so the rows split in two sides. A side with <= rows
+// is the suppliers and a side with >= rows is the customers; if all rows are equalities, the side of
+// the first row is the suppliers. The missing and the fixed routes get the prohibitive cost.
+// The inequalities become the ranges of the amounts.
+fn transportation(model: Model) -> Result<Imported, Error> {
+    if model.maximize {
+        return Err(NotTransportation);
+    }
+    let n = model.rows.len();
+    let mut ends: Vec<Vec<usize>> = vec![Vec::new(); model.vars.len()];
+    for (r, row) in model.rows.iter().enumerate() {
+        for &(v, a) in &row.1 {
+            if a != 0.0 {
+                if a != 1.0 {
+                    return Err(NotTransportation);
+                }
+                ends[v].push(r);
+            }
+        }
+    }
+    if ends.iter().any(|e| e.len() != 2) {
+        return Err(NotTransportation);
+    }
+
+    let mut adjacent = vec![Vec::new(); n];
+    for e in &ends {
+        adjacent[e[0]].push(e[1]);
+        adjacent[e[1]].push(e[0]);
+    }
+    let mut parity: Vec<Option<bool>> = vec![None; n];
+    let mut supplier = vec![false; n];
+    for start in 0..n {
+        if parity[start].is_some() {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut queue = VecDeque::new();
+        parity[start] = Some(false);
+        queue.push_back(start);
+        while let Some(r) = queue.pop_front() {
+            let p = parity[r].unwrap();
+            for &w in &adjacent[r] {
+                match parity[w] {
+                    None => {
+                        parity[w] = Some(!p);
+                        component.push(w);
+                        queue.push_back(w);
+                    }
+                    Some(q) if q == p => return Err(NotTransportation),
+                    _ => {}
+                }
+            }
+        }
+
+        // The rows of the suppliers have the parity different from flip
+        let mut flip = None;
+        for &r in &component {
+            let p = parity[r].unwrap();
+            let wanted = match model.rows[r].2 {
+                Relation::LessOrEqual => !p,
+                Relation::GreaterOrEqual => p,
+                Relation::Equal => continue,
+            };
+            if flip.is_some_and(|f| f != wanted) {
+                return Err(NotTransportation);
+            }
+            flip = Some(wanted);
+        }
+        let flip = flip.unwrap_or(true);
+        for &r in &component {
+            supplier[r] = parity[r].unwrap() != flip;
+        }
+    }
+
+    let suppliers: Vec<usize> = (0..n).filter(|&r| supplier[r]).collect();
+    let customers: Vec<usize> = (0..n).filter(|&r| !supplier[r]).collect();
+    let mut position = vec![0; n];
+    for side in [&suppliers, &customers] {
+        for (k, &r) in side.iter().enumerate() {
+            position[r] = k;
+        }
+    }
+    let a: Vec<u64> = suppliers.iter().map(|&r| amount(model.rows[r].3)).collect::<Result<_, _>>()?;
+    let b: Vec<u64> = customers.iter().map(|&r| amount(model.rows[r].3)).collect::<Result<_, _>>()?;
+
+    let mut c: Vec<Vec<Option<u64>>> = vec![vec![None; b.len()]; a.len()];
+    let mut seen = vec![vec![false; b.len()]; a.len()];
+    for (v, e) in ends.iter().enumerate() {
+        let (s, d) = if supplier[e[0]] { (e[0], e[1]) } else { (e[1], e[0]) };
+        let (i, j) = (position[s], position[d]);
+        if seen[i][j] {
+            return Err(NotTransportation);
+        }
+        seen[i][j] = true;
+        let cost = amount(model.cost[v])?;
+        if !model.fixed[v] {
+            c[i][j] = Some(cost);
+        }
+    }
+    let known: Vec<Vec<u64>> = c.iter().map(|row| row.iter().map(|x| x.unwrap_or(0)).collect()).collect();
+    let sum_s: u64 = a.iter().sum();
+    let m = checked_prohibitive_cost(&known, sum_s)?;
+    let forbidden: Vec<(usize, usize)> = (0..a.len() * b.len()).map(|n| (n / b.len(), n % b.len())).filter(|&(i, j)| c[i][j].is_none()).collect();
+    let c: Vec<Vec<u64>> = c.iter().map(|row| row.iter().map(|x| x.unwrap_or(m)).collect()).collect();
+
+    let label = |r: usize, prefix: &str| {
+        let name = &model.rows[r].0;
+        name.strip_prefix(prefix).filter(|rest| !rest.is_empty()).unwrap_or(name).to_string()
+    };
+    let supplier_labels = suppliers.iter().map(|&r| label(r, "s_")).collect();
+    let customer_labels = customers.iter().map(|&r| label(r, "d_")).collect();
+
+    let equal = model.rows.iter().all(|row| row.2 == Relation::Equal);
+    if equal && sum_s != b.iter().sum::<u64>() {
+        return Err(Infeasible);
+    }
+    // A >= row is never needed to take more than the other side offers
+    let sum_d: u64 = b.iter().sum();
+    let range = |r: usize, x: u64, other: u64| match model.rows[r].2 {
+        Relation::Equal => (x, x),
+        Relation::LessOrEqual => (0, x),
+        Relation::GreaterOrEqual => (x, std::cmp::max(x, other)),
+    };
+    let build = |c: Vec<Vec<u64>>| if equal {
+        Transportation::new(a.clone(), b.clone(), c)
+    } else {
+        Transportation::with_ranges(suppliers.iter().zip(a.iter()).map(|(&r, &x)| range(r, x, sum_d)).collect(),
+                                    customers.iter().zip(b.iter()).map(|(&r, &x)| range(r, x, sum_s)).collect(),
+                                    c)
+    };
+
+    // The missing routes must stay unused: the problem where only they cost anything has a free plan
+    if !forbidden.is_empty() {
+        let mut check = build((0..a.len()).map(|i| (0..b.len()).map(|j| if forbidden.contains(&(i, j)) { 1 } else { 0 }).collect()).collect())?;
+        check.potential_method();
+        if check.total_cost() > 0 {
+            return Err(Infeasible);
+        }
+    }
+    let mut t = build(c)?;
+    t.forbidden = forbidden;
+    Ok((t, supplier_labels, customer_labels))
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Name(String),
+    Number(f64),
+    Sign(f64),
+    Relation(Relation),
+    Colon,
+}
+
+// The characters allowed in the names of the LP format besides letters and digits
+const NAME_CHARS: &str = "!\"#$%&()/,.;?@_`'{}|~[]";
+
+fn tokens(line: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut tokens = Vec::new();
+    let mut k = 0;
+    while k < chars.len() {
+        let ch = chars[k];
+        let start = k;
+        k += 1;
+        match ch {
+            _ if ch.is_whitespace() => {}
+            '+' => tokens.push(Token::Sign(1.0)),
+            '-' => tokens.push(Token::Sign(-1.0)),
+            ':' => tokens.push(Token::Colon),
+            '<' | '>' | '=' => {
+                let next = chars.get(k).cloned();
+                let relation = match (ch, next) {
+                    ('<', _) | ('=', Some('<')) => Relation::LessOrEqual,
+                    ('>', _) | ('=', Some('>')) => Relation::GreaterOrEqual,
+                    _ => Relation::Equal,
+                };
+                if next == Some('=') || ch == '=' && (next == Some('<') || next == Some('>')) {
+                    k += 1;
+                }
+                tokens.push(Token::Relation(relation));
+            }
+            _ if ch.is_ascii_digit() || ch == '.' => {
+                while k < chars.len() && (chars[k].is_ascii_digit() || chars[k] == '.') {
+                    k += 1;
+                }
+                // The exponent
+                if k < chars.len() && (chars[k] == 'e' || chars[k] == 'E') {
+                    let digits = if k + 1 < chars.len() && (chars[k + 1] == '+' || chars[k + 1] == '-') { k + 2 } else { k + 1 };
+                    if digits < chars.len() && chars[digits].is_ascii_digit() {
+                        k = digits;
+                        while k < chars.len() && chars[k].is_ascii_digit() {
+                            k += 1;
+                        }
+                    }
+                }
+                let number: String = chars[start..k].iter().collect();
+                tokens.push(Token::Number(number.parse().map_err(|_| Parse)?));
+            }
+            _ if ch.is_alphabetic() || NAME_CHARS.contains(ch) => {
+                while k < chars.len() && (chars[k].is_alphanumeric() || NAME_CHARS.contains(chars[k])) {
+                    k += 1;
+                }
+                tokens.push(Token::Name(chars[start..k].iter().collect()));
+            }
+            _ => return Err(Parse),
+        }
+    }
+    Ok(tokens)
+}
+
+// The terms of a linear expression up to a relation or the end
+fn terms(tokens: &[Token], pos: &mut usize, model: &mut Model) -> Result<Vec<(usize, f64)>, Error> {
+    let mut terms: Vec<(usize, f64)> = Vec::new();
+    while *pos < tokens.len() {
+        let start = *pos;
+        let mut sign = 1.0;
+        while let Some(&Token::Sign(s)) = tokens.get(*pos) {
+            sign *= s;
+            *pos += 1;
+        }
+        let mut coefficient = 1.0;
+        if let Some(&Token::Number(x)) = tokens.get(*pos) {
+            coefficient = x;
+            *pos += 1;
+        }
+        match tokens.get(*pos) {
+            Some(Token::Name(name)) => {
+                let v = model.var(name);
+                match terms.iter_mut().find(|t| t.0 == v) {
+                    Some(t) => t.1 += sign * coefficient,
+                    None => terms.push((v, sign * coefficient)),
+                }
+                *pos += 1;
+            }
+            Some(&Token::Relation(_)) if *pos == start => break,
+            _ => return Err(Parse),
+        }
+    }
+    Ok(terms)
+}
+
+// A number with its sign, the infinity is written as inf or infinity
+fn value(tokens: &[Token], pos: &mut usize) -> Result<f64, Error> {
+    let mut sign = 1.0;
+    while let Some(&Token::Sign(s)) = tokens.get(*pos) {
+        sign *= s;
+        *pos += 1;
+    }
+    *pos += 1;
+    match tokens.get(*pos - 1) {
+        Some(&Token::Number(x)) => Ok(sign * x),
+        Some(Token::Name(name)) if name.eq_ignore_ascii_case("inf") || name.eq_ignore_ascii_case("infinity") => Ok(sign * f64::INFINITY),
+        _ => Err(Parse),
+    }
+}
+
+fn bound(model: &mut Model, name: &str, relation: Relation, x: f64) -> Result<(), Error> {
+    match relation {
+        Relation::LessOrEqual => model.upper(name, x),
+        Relation::GreaterOrEqual => model.lower(name, x),
+        Relation::Equal => model.lower(name, x).and_then(|_| model.upper(name, x)),
+    }
+}
+
+// A bound is x rel value, value rel x, value rel x rel value or x free
+fn bounds(model: &mut Model, tokens: &[Token]) -> Result<(), Error> {
+    let flip = |r: Relation| match r {
+        Relation::LessOrEqual => Relation::GreaterOrEqual,
+        Relation::GreaterOrEqual => Relation::LessOrEqual,
+        Relation::Equal => Relation::Equal,
+    };
+    match tokens.first() {
+        Some(Token::Name(name)) if !name.eq_ignore_ascii_case("inf") && !name.eq_ignore_ascii_case("infinity") => {
+            match tokens.get(1) {
+                Some(Token::Name(word)) if word.eq_ignore_ascii_case("free") && tokens.len() == 2 => model.lower(name, f64::NEG_INFINITY),
+                Some(&Token::Relation(r)) => {
+                    let mut pos = 2;
+                    let x = value(tokens, &mut pos)?;
+                    if pos != tokens.len() {
+                        return Err(Parse);
+                    }
+                    bound(model, name, r, x)
+                }
+                _ => Err(Parse),
+            }
+        }
+        _ => {
+            let mut pos = 0;
+            let x = value(tokens, &mut pos)?;
+            let (r, name) = match (tokens.get(pos), tokens.get(pos + 1)) {
+                (Some(&Token::Relation(r)), Some(Token::Name(name))) => (r, name.clone()),
+                _ => return Err(Parse),
+            };
+            bound(model, &name, flip(r), x)?;
+            pos += 2;
+            if pos == tokens.len() {
+                return Ok(());
+            }
+            let r = match tokens.get(pos) {
+                Some(&Token::Relation(r)) => r,
+                _ => return Err(Parse),
+            };
+            pos += 1;
+            let y = value(tokens, &mut pos)?;
+            if pos != tokens.len() {
+                return Err(Parse);
+            }
+            bound(model, &name, r, y)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Objective,
+    Constraints,
+    Bounds,
+    Integers,
+    End,
+}
+
+// The section that the line starts and the rest of the line
+fn section(line: &str) -> Option<(Section, &str)> {
+    let line = line.trim_start();
+    let lower = line.to_ascii_lowercase();
+    for &keyword in &["subject to", "such that"] {
+        if lower.starts_with(keyword) {
+            return Some((Section::Constraints, &line[keyword.len()..]));
+        }
+    }
+    let word = lower.split_whitespace().next().unwrap_or("");
+    let section = match word {
+        "minimize" | "minimise" | "minimum" | "min" | "maximize" | "maximise" | "maximum" | "max" => Section::Objective,
+        "st" | "s.t." | "st." => Section::Constraints,
+        "bounds" | "bound" => Section::Bounds,
+        "general" | "generals" | "gen" | "integer" | "integers" => Section::Integers,
+        "end" => Section::End,
+        _ => return None,
+    };
+    Some((section, &line[word.len()..]))
+}
+
+// Read a model in the CPLEX LP format
+pub fn parse_lp(text: &str) -> Result<Imported, Error> {
+    let mut model = Model::new();
+    let mut current = None;
+    let mut objective = Vec::new();
+    let mut constraints = Vec::new();
+
+    for line in text.lines() {
+        let line = line.split('\\').next().unwrap_or("");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rest = match section(line) {
+            Some((s, rest)) => {
+                if s == Section::Objective {
+                    model.maximize = line.trim_start().to_lowercase().starts_with("max");
+                }
+                current = Some(s);
+                rest
+            }
+            None => line,
+        };
+        let line_tokens = tokens(rest)?;
+        match current {
+            Some(Section::Objective) => objective.extend(line_tokens),
+            Some(Section::Constraints) => constraints.extend(line_tokens),
+            Some(Section::Bounds) if !line_tokens.is_empty() => bounds(&mut model, &line_tokens)?,
+            Some(Section::Bounds) | Some(Section::Integers) => {}
+            Some(Section::End) => break,
+            // The binary and other sections are not a part of the transportation problem
+            None => {
+                let word = line.split_whitespace().next().unwrap_or("").to_lowercase();
+                if ["binary", "binaries", "bin", "semi-continuous", "semis", "semi", "sos"].contains(&word.as_str()) {
+                    return Err(NotTransportation);
+                }
+                return Err(Parse);
+            }
+        }
+    }
+
+    // The objective may be named
+    let mut pos = 0;
+    if let (Some(&Token::Name(_)), Some(&Token::Colon)) = (objective.first(), objective.get(1)) {
+        pos = 2;
+    }
+    for (v, c) in terms(&objective, &mut pos, &mut model)? {
+        model.cost[v] = c;
+    }
+    if pos != objective.len() {
+        return Err(Parse);
+    }
+
+    let mut pos = 0;
+    while pos < constraints.len() {
+        let mut name = format!("R{}", model.rows.len() + 1);
+        if let (Some(Token::Name(n)), Some(&Token::Colon)) = (constraints.get(pos), constraints.get(pos + 1)) {
+            name = n.clone();
+            pos += 2;
+        }
+        let row_terms = terms(&constraints, &mut pos, &mut model)?;
+        let relation = match constraints.get(pos) {
+            Some(&Token::Relation(r)) => r,
+            _ => return Err(Parse),
+        };
+        pos += 1;
+        let rhs = value(&constraints, &mut pos)?;
+        model.rows.push((name, row_terms, relation, rhs));
+    }
+    transportation(model)
+}
+
+// Read a model in the free MPS format
+pub fn parse_mps(text: &str) -> Result<Imported, Error> {
+    let mut model = Model::new();
+    let mut section = String::new();
+    let mut objective: Option<String> = None;
+    // The rows by their names, the free rows other than the objective are None
+    let mut rows: HashMap<String, Option<usize>> = HashMap::new();
+
+    for line in text.lines() {
+        if line.trim().is_empty() || line.starts_with('*') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if !line.starts_with(char::is_whitespace) {
+            section = fields[0].to_uppercase();
+            match section.as_str() {
+                "NAME" | "ROWS" | "COLUMNS" | "RHS" | "RANGES" | "BOUNDS" => continue,
+                "OBJSENSE" => {
+                    if let Some(sense) = fields.get(1) {
+                        model.maximize = sense.to_uppercase().starts_with("MAX");
+                    }
+                    continue;
+                }
+                "ENDATA" => break,
+                _ => return Err(Parse),
+            }
+        }
+
+        match section.as_str() {
+            "OBJSENSE" => model.maximize = fields[0].to_uppercase().starts_with("MAX"),
+            "ROWS" => {
+                if fields.len() != 2 {
+                    return Err(Parse);
+                }
+                let relation = match fields[0].to_uppercase().as_str() {
+                    "N" => {
+                        if objective.is_none() {
+                            objective = Some(fields[1].to_string());
+                        } else {
+                            rows.insert(fields[1].to_string(), None);
+                        }
+                        continue;
+                    }
+                    "E" => Relation::Equal,
+                    "L" => Relation::LessOrEqual,
+                    "G" => Relation::GreaterOrEqual,
+                    _ => return Err(Parse),
+                };
+                rows.insert(fields[1].to_string(), Some(model.rows.len()));
+                model.rows.push((fields[1].to_string(), Vec::new(), relation, 0.0));
+            }
+            "COLUMNS" => {
+                if fields.get(1) == Some(&"'MARKER'") {
+                    continue;
+                }
+                if fields.len() != 3 && fields.len() != 5 {
+                    return Err(Parse);
+                }
+                let v = model.var(fields[0]);
+                for pair in fields[1..].chunks(2) {
+                    let x: f64 = pair[1].parse().map_err(|_| Parse)?;
+                    if objective.as_deref() == Some(pair[0]) {
+                        model.cost[v] += x;
+                        continue;
+                    }
+                    match rows.get(pair[0]) {
+                        Some(&Some(r)) => model.rows[r].1.push((v, x)),
+                        Some(&None) => {}
+                        None => return Err(Parse),
+                    }
+                }
+            }
+            "RHS" => {
+                // The name of the set may be omitted
+                let pairs = if fields.len() % 2 == 1 { &fields[1..] } else { &fields[..] };
+                for pair in pairs.chunks(2) {
+                    if pair.len() != 2 {
+                        return Err(Parse);
+                    }
+                    let x: f64 = pair[1].parse().map_err(|_| Parse)?;
+                    if objective.as_deref() == Some(pair[0]) {
+                        if x != 0.0 {
+                            return Err(NotTransportation);
+                        }
+                        continue;
+                    }
+                    match rows.get(pair[0]) {
+                        Some(&Some(r)) => model.rows[r].3 = x,
+                        Some(&None) => {}
+                        None => return Err(Parse),
+                    }
+                }
+            }
+            "RANGES" => return Err(NotTransportation),
+            "BOUNDS" => {
+                let kind = fields[0].to_uppercase();
+                let with_value = !["FR", "MI", "PL", "BV"].contains(&kind.as_str());
+                let (name, x) = match (with_value, fields.len()) {
+                    (true, 4) => (fields[2], fields[3].parse().map_err(|_| Parse)?),
+                    (true, 3) => (fields[1], fields[2].parse().map_err(|_| Parse)?),
+                    (false, 3) => (fields[2], 0.0),
+                    (false, 2) => (fields[1], 0.0),
+                    _ => return Err(Parse),
+                };
+                match kind.as_str() {
+                    "UP" | "UI" => model.upper(name, x)?,
+                    "LO" | "LI" => model.lower(name, x)?,
+                    "FX" => {
+                        model.lower(name, x)?;
+                        model.upper(name, x)?;
+                    }
+                    "FR" | "MI" => model.lower(name, f64::NEG_INFINITY)?,
+                    "PL" => {}
+                    "BV" | "SC" => return Err(NotTransportation),
+                    _ => return Err(Parse),
+                }
+            }
+            _ => return Err(Parse),
+        }
+    }
+    transportation(model)
+}
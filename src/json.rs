@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+use super::Transportation;
+use super::Error::{self, *};
+
+// The way of solving the problem, the names are the ones of the binary's options
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+pub struct JsonOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solver: Option<String>,
+}
+
+// The problem: the supply, the demand, the matrix of costs and the labels of the suppliers and the
+// customers, the labels may be omitted
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct JsonProblem {
+    pub supply: Vec<u64>,
+    pub demand: Vec<u64>,
+    pub cost: Vec<Vec<u64>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppliers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub customers: Vec<String>,
+    #[serde(default)]
+    pub options: JsonOptions,
+}
+
+impl JsonProblem {
+    pub fn from_json(text: &str) -> Result<JsonProblem, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+    // The labels must be given for every supplier and customer or for none
+    pub fn transportation(&self) -> Result<Transportation, Error> {
+        if !self.suppliers.is_empty() && self.suppliers.len() != self.supply.len() ||
+           !self.customers.is_empty() && self.customers.len() != self.demand.len() {
+            return Err(NumOfLabels);
+        }
+        Transportation::new(self.supply.clone(), self.demand.clone(), self.cost.clone())
+    }
+}
+
+// The solution: the plan and the cost without the dummies, the potentials of the rows and the columns
+// of the balanced problem with their names and the number of iterations if a pricing rule is given
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct JsonSolution {
+    pub plan: Vec<Vec<u64>>,
+    pub cost: u64,
+    pub potentials: JsonPotentials,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppliers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub customers: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct JsonPotentials {
+    pub u: Vec<i64>,
+    pub v: Vec<i64>,
+    // The rows and the columns of the balanced problem, a split supplier or customer names its parts alike
+    #[serde(default)]
+    pub rows: Vec<String>,
+    #[serde(default)]
+    pub cols: Vec<String>,
+}
+
+impl JsonSolution {
+    // The solution of the solved problem
+    pub fn new(t: &Transportation, iterations: Option<usize>, suppliers: &[String], customers: &[String]) -> JsonSolution {
+        let (u, v) = t.potentials();
+        let (rows, cols) = t.names(suppliers, customers);
+        JsonSolution {
+            plan: t.original_plan(),
+            cost: t.total_cost(),
+            potentials: JsonPotentials { u, v, rows, cols },
+            iterations,
+            suppliers: suppliers.to_vec(),
+            customers: customers.to_vec(),
+        }
+    }
+
+    pub fn from_json(text: &str) -> Result<JsonSolution, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
@@ -1,12 +1,66 @@
 extern crate prettytable;
+#[cfg(feature = "json")]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
 use prettytable::Table;
 use prettytable::row::Row;
 use prettytable::cell::Cell;
 
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::ops::{Index, IndexMut};
+use std::time::{Duration, Instant};
 use self::Direction::{Down, Left, Right, Up};
 use self::Error::*;
 
+mod commodity;
+mod cost;
+mod csv;
+mod distance;
+mod echelon;
+mod fixed;
+mod export;
+mod generalized;
+mod import;
+#[cfg(feature = "json")]
+mod json;
+mod location;
+mod objective;
+mod parametric;
+mod period;
+mod pricing;
+mod profit;
+mod range;
+mod robust;
+mod simplex;
+mod solid;
+mod solver;
+mod sparse;
+mod stochastic;
+mod tariff;
+pub use self::commodity::MultiCommodity;
+pub use self::cost::CostSource;
+pub use self::csv::parse_csv;
+pub use self::distance::{location_costs, Location, LocationCosts, Metric};
+pub use self::echelon::TwoStage;
+pub use self::fixed::FixedCharge;
+pub use self::generalized::Generalized;
+pub use self::import::{parse_lp, parse_mps, Imported};
+#[cfg(feature = "json")]
+pub use self::json::{JsonOptions, JsonPotentials, JsonProblem, JsonSolution};
+pub use self::location::FacilityLocation;
+pub use self::objective::MultiObjective;
+pub use self::parametric::Parametric;
+pub use self::period::MultiPeriod;
+pub use self::pricing::{Block, CandidateList, Dantzig, FirstNegative, Parallel, Prices, Pricing, SteepestEdge};
+pub use self::profit::Profit;
+pub use self::robust::Robust;
+pub use self::simplex::{minimize, Constraint, Relation};
+pub use self::solid::Solid;
+pub use self::solver::{CostScaling, PotentialMethod, Solver, SuccessiveShortestPath};
+pub use self::sparse::{Route, SparseTransportation};
+pub use self::stochastic::Stochastic;
+pub use self::tariff::RouteTariff;
 #[cfg(test)]
 mod test;
 // Possible directions for cycle
@@ -19,7 +73,7 @@ enum Direction {
     None,
 }
 // The structure for emulation a two-dimensional array
-struct Matrix<T> {
+pub struct Matrix<T> {
     cols: usize,
     data: Vec<T>,
 }
@@ -43,6 +97,15 @@ impl<T> IndexMut<usize> for Matrix<T> {
 }
 
 impl<T> Matrix<T> {
+    // The matrix of the rows of the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Matrix<T> {
+        let mut matrix = Matrix::new(rows.first().map_or(0, Vec::len));
+        for row in rows {
+            matrix.push(row);
+        }
+        matrix
+    }
+
     fn new(columns: usize) -> Matrix<T> {
         Matrix {
             cols: columns,
@@ -55,30 +118,91 @@ impl<T> Matrix<T> {
         self.data.append(&mut vector);
     }
     // The number of rows in the matrix
-    fn rows(&self) -> usize {
-        self.data.len() / self.cols
+    pub fn rows(&self) -> usize {
+        self.data.len().checked_div(self.cols).unwrap_or(0)
     }
     // The number of columns in the matrix
-    fn cols(&self) -> usize {
+    pub fn cols(&self) -> usize {
         self.cols
     }
 }
 
-impl Matrix<Direction> {
+// The basic cells of the plan. Every row keeps its cells with the amounts and every column
+// the rows of its cells, so the memory grows with the basis and not with the matrix of costs.
+#[derive(Clone, PartialEq, Debug)]
+struct Basis {
+    rows: Vec<BTreeMap<usize, u64>>,
+    cols: Vec<BTreeSet<usize>>,
+}
+
+impl Basis {
+    fn new(rows: usize, cols: usize) -> Basis {
+        Basis {
+            rows: vec![BTreeMap::new(); rows],
+            cols: vec![BTreeSet::new(); cols],
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn cols(&self) -> usize {
+        self.cols.len()
+    }
+    // The amount of the cell, None if the cell is not basic
+    fn get(&self, i: usize, j: usize) -> Option<u64> {
+        self.rows[i].get(&j).cloned()
+    }
+
+    fn set(&mut self, i: usize, j: usize, x: Option<u64>) {
+        match x {
+            Some(x) => {
+                self.rows[i].insert(j, x);
+                self.cols[j].insert(i);
+            }
+            None => {
+                self.rows[i].remove(&j);
+                self.cols[j].remove(&i);
+            }
+        }
+    }
+    // The number of basic cells
+    fn len(&self) -> usize {
+        self.rows.iter().map(BTreeMap::len).sum()
+    }
+    // The basic cells row by row
+    fn cells<'a>(&'a self) -> impl Iterator<Item = (usize, usize, u64)> + 'a {
+        self.rows.iter().enumerate().flat_map(|(i, row)| row.iter().map(move |(&j, &x)| (i, j, x)))
+    }
+}
+
+// The directions of the cells visited by the cycle detection, the other cells have none
+struct Directions(HashMap<(usize, usize), Direction>);
+
+impl Directions {
+    fn get(&self, i: usize, j: usize) -> Direction {
+        self.0.get(&(i, j)).cloned().unwrap_or(Direction::None)
+    }
+
+    fn set(&mut self, i: usize, j: usize, d: Direction) {
+        self.0.insert((i, j), d);
+    }
+
     fn iter<'a>(&'a self, i: usize, j: usize) -> MatrixIter<'a> {
         MatrixIter {
             state: self,
             start_i: i,
             start_j: j,
-            i: i,
-            j: j,
+            i,
+            j,
             f: false,
         }
     }
 }
 
 struct MatrixIter<'a> {
-    state: &'a Matrix<Direction>,
+    state: &'a Directions,
     start_i: usize,
     start_j: usize,
     i: usize,
@@ -109,8 +233,8 @@ impl<'a> Iterator for MatrixIter<'a> {
         }
 
         let buf = (self.i, self.j);
-        let d = self.state[self.i][self.j];
-        while self.state[self.i][self.j] == d || self.state[self.i][self.j] == Direction::None {
+        let d = self.state.get(self.i, self.j);
+        while self.state.get(self.i, self.j) == d || self.state.get(self.i, self.j) == Direction::None {
             self.move_by(d);
         }
         Some(buf)
@@ -123,34 +247,85 @@ pub enum Error {
     NumOfSupOrCust,
     NumOfRows,
     NumOfCols,
+    Range,
+    Infeasible,
+    Tariff,
+    NumOfProducts,
+    NumOfConveyances,
+    NumOfPeriods,
+    Gain,
+    Unbounded,
+    NumOfObjectives,
+    NumOfScenarios,
+    RouteIndex,
+    Parse,
+    NotTransportation,
+    Overflow,
+    NotOptimal,
+    NumOfLabels,
+    Csv,
+    NotConverged,
+    Parameter,
+    Rate,
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        use std::error::Error;
-        f.write_str(self.description())
-    }
-}
-
-impl std::error::Error for Error {
-    fn description(&self) -> &str {
-        match *self {
+        f.write_str(match *self {
             NumOfSupOrCust => "invalid number of suppliers or customers",
             NumOfRows => "invalid number of rows in the matrix of costs",
             NumOfCols => "invalid number of columns in the matrix of costs",
-        }
+            Range => "the minimum of a range is greater than its maximum",
+            Infeasible => "the problem has no feasible solution",
+            Tariff => "the tariff of a route is invalid or its costs decrease",
+            NumOfProducts => "invalid number of products",
+            NumOfConveyances => "invalid number of conveyances",
+            NumOfPeriods => "invalid number of periods",
+            Gain => "a gain factor is not positive",
+            Unbounded => "the objective is unbounded",
+            NumOfObjectives => "invalid number of objectives",
+            NumOfScenarios => "invalid number of scenarios or their weights",
+            RouteIndex => "a route refers to a missing supplier or customer or is repeated",
+            Parse => "the file is not a valid LP or MPS model",
+            NotTransportation => "the model is not a transportation problem",
+            Overflow => "the costs are too large",
+            NotOptimal => "the plan of the solver is not optimal",
+            NumOfLabels => "invalid number of labels of suppliers or customers",
+            Csv => "the file is not a valid table of costs",
+            NotConverged => "no feasible plan was found, though the problem has one",
+            Parameter => "the range of the parameter is empty or a denominator is zero",
+            Rate => "the cost of a unit of distance is negative or not a finite number",
+        })
     }
 }
 
-// The main structure
-pub struct Transportation {
+impl std::error::Error for Error {}
+
+// The main structure. The costs are the dense matrix unless another source is given.
+pub struct Transportation<C = Matrix<u64>> {
     supply: Vec<u64>,
     demand: Vec<u64>,
-    cost: Matrix<u64>,
-    trans: Matrix<Option<u64>>,
+    cost: C,
+    trans: Basis,
+    // The number of suppliers and customers without the dummies
+    shape: (usize, usize),
+    ranges: Option<range::Ranges>,
+    tariffs: Option<tariff::Tariffs>,
+    // The routes of the original problem that don't exist, they have the prohibitive cost
+    forbidden: Vec<(usize, usize)>,
 }
 
-impl Transportation {
+impl<C: CostSource> Transportation<C> {
+    // The cost of the cell, the dummies that the source doesn't know about cost nothing
+    fn cost_of(&self, i: usize, j: usize) -> u64 {
+        let (rows, cols) = self.cost.shape();
+        if i < rows && j < cols {
+            self.cost.cost(i, j)
+        } else {
+            0
+        }
+    }
+
     fn least_cost_method(&mut self) {
         let mut a = self.supply.to_vec();
         let mut b = self.demand.to_vec();
@@ -159,31 +334,31 @@ impl Transportation {
         loop {
             min = None;
 
-            for i in 0..self.cost.rows() {
-                if a[i] == 0 {
+            for (i, &ai) in a.iter().enumerate() {
+                if ai == 0 {
                     continue;
                 }
-                for j in 0..self.cost.cols() {
-                    if b[j] == 0 {
+                for (j, &bj) in b.iter().enumerate() {
+                    if bj == 0 {
                         continue;
                     }
                     if let Some((_, _, v)) = min {
-                        if v > self.cost[i][j] {
-                            min = Some((i, j, self.cost[i][j]));
+                        if v > self.cost_of(i, j) {
+                            min = Some((i, j, self.cost_of(i, j)));
                         }
                     } else {
-                        min = Some((i, j, self.cost[i][j]));
+                        min = Some((i, j, self.cost_of(i, j)));
                     }
                 }
             }
 
             if let Some((i, j, _)) = min {
                 if a[i] >= b[j] {
-                    self.trans[i][j] = Some(b[j]);
+                    self.trans.set(i, j, Some(b[j]));
                     a[i] -= b[j];
                     b[j] = 0;
                 } else {
-                    self.trans[i][j] = Some(a[i]);
+                    self.trans.set(i, j, Some(a[i]));
                     b[j] -= a[i];
                     a[i] = 0;
                 }
@@ -192,65 +367,33 @@ impl Transportation {
             }
         }
     }
-    // Detect cycle via dfs and build a matrix of directions
-    // If the direction of the trans_state[i][j] isn't None, the cycle is found
-    fn cycle_detection(&self, i: usize, j: usize) -> Matrix<Direction> {
-        let mut trans_state = Matrix::new(self.trans.cols());
-        for _ in 0..self.trans.rows() {
-            trans_state.push(vec![Direction::None;self.trans.cols()]);
-        }
-
-        fn _cycle_detection(x: &Matrix<Option<u64>>, x_state: &mut Matrix<Direction>, i: usize, j: usize) {
-            if x_state[i][j] != Down {
-                for k in (0..i).rev() {
-                    if x[k][j] != None {
-                        if x_state[k][j] == Direction::None {
-                            x_state[k][j] = Up;
-                            _cycle_detection(x, x_state, k, j);
-                        } else {
-                            return;
-                        }
-                        break;
-                    }
-                }
-            }
-            if x_state[i][j] != Up {
-                for k in i + 1..x.rows() {
-                    if x[k][j] != None {
-                        if x_state[k][j] == Direction::None {
-                            x_state[k][j] = Down;
-                            _cycle_detection(x, x_state, k, j);
-                        } else {
-                            return;
-                        }
-                        break;
-                    }
-                }
-            }
-            if x_state[i][j] != Right {
-                for k in (0..j).rev() {
-                    if x[i][k] != None {
-                        if x_state[i][k] == Direction::None {
-                            x_state[i][k] = Left;
-                            _cycle_detection(x, x_state, i, k);
-                        } else {
-                            return;
-                        }
-                        break;
-                    }
+    // Detect cycle via dfs and find the directions of the cells.
+    // If the direction of the cell (i, j) isn't None, the cycle is found
+    fn cycle_detection(&self, i: usize, j: usize) -> Directions {
+        let mut trans_state = Directions(HashMap::new());
+
+        fn _cycle_detection(x: &Basis, x_state: &mut Directions, i: usize, j: usize) {
+            // The nearest basic cells up, down, left and right of the cell
+            let up = x.cols[j].range(..i).next_back().map(|&k| (k, j, Up));
+            let down = x.cols[j].range(i + 1..).next().map(|&k| (k, j, Down));
+            let left = x.rows[i].range(..j).next_back().map(|(&k, _)| (i, k, Left));
+            let right = x.rows[i].range(j + 1..).next().map(|(&k, _)| (i, k, Right));
+            let opposite = |d: Direction| match d {
+                Up => Down,
+                Down => Up,
+                Left => Right,
+                Right => Left,
+                Direction::None => Direction::None,
+            };
+            for (k, l, d) in vec![up, down, left, right].into_iter().flatten() {
+                if x_state.get(i, j) == opposite(d) {
+                    continue;
                 }
-            }
-            if x_state[i][j] != Left {
-                for k in j + 1..x.cols() {
-                    if x[i][k] != None {
-                        if x_state[i][k] == Direction::None {
-                            x_state[i][k] = Right;
-                            _cycle_detection(x, x_state, i, k);
-                        } else {
-                            return;
-                        }
-                        break;
-                    }
+                if x_state.get(k, l) == Direction::None {
+                    x_state.set(k, l, d);
+                    _cycle_detection(x, x_state, k, l);
+                } else {
+                    return;
                 }
             }
         }
@@ -262,18 +405,19 @@ impl Transportation {
     // If the number of basic cells in the transportation plan is less then
     // m + n - 1, it needs to replenish
     fn replenish(&mut self) {
-        let number = self.trans.data.iter().filter(|&&a| a != None).count();
-
-        if number < self.trans.rows() + self.trans.cols() - 1 {
-            for i in 0..self.trans.rows() {
-                for j in 0..self.trans.cols() {
-                    if self.trans[i][j] == None {
-                        self.trans[i][j] = Some(0);
-                        if self.cycle_detection(i, j)[i][j] != Direction::None {
-                            self.trans[i][j] = None;
-                        } else {
-                            return;
-                        }
+        let mut number = self.trans.len();
+
+        for i in 0..self.trans.rows() {
+            for j in 0..self.trans.cols() {
+                if number >= self.trans.rows() + self.trans.cols() - 1 {
+                    return;
+                }
+                if self.trans.get(i, j).is_none() {
+                    self.trans.set(i, j, Some(0));
+                    if self.cycle_detection(i, j).get(i, j) != Direction::None {
+                        self.trans.set(i, j, None);
+                    } else {
+                        number += 1;
                     }
                 }
             }
@@ -281,45 +425,46 @@ impl Transportation {
     }
     // Recursive calculation of potentials
     fn calculation_of_potentials(&self) -> (Vec<i64>, Vec<i64>) {
+        self.potentials_for(|i, j| self.cost_of(i, j))
+    }
+    // The potentials of the current basis for any costs
+    fn potentials_for<F: Fn(usize, usize) -> u64>(&self, cost: F) -> (Vec<i64>, Vec<i64>) {
         let mut u = vec![0;self.trans.rows()];
         let mut v = vec![0;self.trans.cols()];
 
-        fn calculation_of_potentials_h(c: &Matrix<u64>, x: &Matrix<Option<u64>>, u: &mut [i64], v: &mut [i64], i: usize, j: usize) {
-            for k in 0..x.cols() {
-                if x[i][k] != None {
-                    v[k] = c[i][k] as i64 - u[i];
-                    if k != j {
-                        calculation_of_potentials_v(c, x, u, v, i, k);
-                    }
+        fn calculation_of_potentials_h(c: &dyn Fn(usize, usize) -> u64, x: &Basis, u: &mut [i64], v: &mut [i64], i: usize, j: usize) {
+            for &k in x.rows[i].keys() {
+                v[k] = c(i, k) as i64 - u[i];
+                if k != j {
+                    calculation_of_potentials_v(c, x, u, v, i, k);
                 }
             }
         }
 
-        fn calculation_of_potentials_v(c: &Matrix<u64>, x: &Matrix<Option<u64>>, u: &mut [i64], v: &mut [i64], i: usize, j: usize) {
-            for k in 0..x.rows() {
-                if x[k][j] != None {
-                    u[k] = c[k][j] as i64 - v[j];
-                    if k != i {
-                        calculation_of_potentials_h(c, x, u, v, k, j);
-                    }
+        fn calculation_of_potentials_v(c: &dyn Fn(usize, usize) -> u64, x: &Basis, u: &mut [i64], v: &mut [i64], i: usize, j: usize) {
+            for &k in &x.cols[j] {
+                u[k] = c(k, j) as i64 - v[j];
+                if k != i {
+                    calculation_of_potentials_h(c, x, u, v, k, j);
                 }
             }
         }
 
-        calculation_of_potentials_h(&self.cost, &self.trans, &mut u, &mut v, 0, 0);
-        calculation_of_potentials_v(&self.cost, &self.trans, &mut u, &mut v, 0, 0);
+        calculation_of_potentials_h(&cost, &self.trans, &mut u, &mut v, 0, 0);
+        calculation_of_potentials_v(&cost, &self.trans, &mut u, &mut v, 0, 0);
 
         (u, v)
     }
     // Check for optimality
-    // If the transportation plan is not optimal, then return the minimum of difference
+    // If the transportation plan is not optimal, then return the minimum of difference.
+    // The differences are computed cell by cell, so the costs are queried only once per cell.
     fn check(&self) -> Option<(usize, usize, i64)> {
         let mut min = None;
         let (u, v) = self.calculation_of_potentials();
 
-        for i in 0..self.cost.rows() {
-            for j in 0..self.cost.cols() {
-                let d = self.cost[i][j] as i64 - u[i] - v[j];
+        for (i, ui) in u.iter().enumerate() {
+            for (j, vj) in v.iter().enumerate() {
+                let d = self.cost_of(i, j) as i64 - ui - vj;
                 if d < 0 {
                     if let Some((_, _, m)) = min {
                         if m > d {
@@ -335,13 +480,43 @@ impl Transportation {
     }
     // Calculate the total cost
     fn total_cost(&self) -> u64 {
-        let mut z = 0;
-        for (t, c) in self.trans.data.iter().zip(self.cost.data.iter()) {
-            if !t.is_none() {
-                z += t.unwrap() * c;
-            }
+        self.trans.cells().map(|(i, j, x)| x * self.cost_of(i, j)).sum()
+    }
+    // The transportation plan with zeros in the free cells, dummies included
+    fn plan(&self) -> Vec<Vec<u64>> {
+        let mut plan = vec![vec![0; self.trans.cols()]; self.trans.rows()];
+        for (i, j, x) in self.trans.cells() {
+            plan[i][j] = x;
+        }
+        plan
+    }
+    // The plan of the original suppliers and customers, without the dummies and the extra nodes
+    pub fn original_plan(&self) -> Vec<Vec<u64>> {
+        if let Some(ref ranges) = self.ranges {
+            return ranges.plan(self);
+        }
+        if let Some(ref tariffs) = self.tariffs {
+            return tariffs.plan(self);
+        }
+        self.plan().iter().take(self.shape.0).map(|row| row[..self.shape.1].to_vec()).collect()
+    }
+    // The potentials of the rows and the columns of the current basis, the dummies included
+    pub fn potentials(&self) -> (Vec<i64>, Vec<i64>) {
+        self.calculation_of_potentials()
+    }
+    // The names of the rows and the columns of the balanced problem: the labels, A1, B1 and so on
+    // if they are missing, and dummy for the dummies
+    fn names(&self, suppliers: &[String], customers: &[String]) -> (Vec<String>, Vec<String>) {
+        let (rows, cols) = (self.supply.len(), self.demand.len());
+        if let Some(ref ranges) = self.ranges {
+            ranges.names(suppliers, customers)
+        } else if let Some(ref tariffs) = self.tariffs {
+            tariffs.names(suppliers, customers, (rows, cols))
+        } else {
+            let shape = self.shape;
+            let name = |k: usize, size: usize, labels: &[String], prefix: &str| if k < size { label(labels, k, prefix) } else { "dummy".to_string() };
+            ((0..rows).map(|i| name(i, shape.0, suppliers, "A")).collect(), (0..cols).map(|j| name(j, shape.1, customers, "B")).collect())
         }
-        z
     }
 
     pub fn potential_method(&mut self) {
@@ -349,69 +524,141 @@ impl Transportation {
         self.replenish();
 
         while let Some((i, j, _)) = self.check() {
-            self.trans[i][j] = Some(0);
-            let trans_state = self.cycle_detection(i, j);
-
-            let mut min = std::u64::MAX;
-            let mut f = true;
-            for x in trans_state.iter(i, j) {
-                if f {
-                    f = false;
-                } else {
-                    if min > self.trans[x.0][x.1].unwrap() {
-                        min = self.trans[x.0][x.1].unwrap();
-                    }
-                    f = true;
+            self.pivot(i, j);
+        }
+    }
+    // The potential method with the given rule of choosing the entering cell.
+    // Return the number of iterations and the time spent.
+    pub fn potential_method_with(&mut self, pricing: &mut dyn Pricing) -> (usize, Duration) {
+        self.iterate(|prices| pricing.entering(prices))
+    }
+    // The same with the parallel rule, its threads share the costs, so the source must be Sync
+    pub fn potential_method_parallel(&mut self, pricing: &mut Parallel) -> (usize, Duration)
+        where C: Sync
+    {
+        self.iterate(|prices| pricing.entering(prices))
+    }
+    // Pivot on the cells chosen by `entering` until it finds none
+    fn iterate<F>(&mut self, mut entering: F) -> (usize, Duration)
+        where F: FnMut(&BasisPrices<C>) -> Option<(usize, usize)>
+    {
+        let start = Instant::now();
+        self.least_cost_method();
+        self.replenish();
+
+        let mut iterations = 0;
+        loop {
+            let (u, v) = self.calculation_of_potentials();
+            match entering(&BasisPrices { t: self, u, v }) {
+                Some((i, j)) => self.pivot(i, j),
+                None => break,
+            }
+            iterations += 1;
+        }
+        (iterations, start.elapsed())
+    }
+    // Bring the cell into the basis by the shift along its cycle
+    fn pivot(&mut self, i: usize, j: usize) {
+        self.pivot_with(i, j, false);
+    }
+    // The leaving cell is the most expensive one that the cycle has emptied or, with Bland's rule,
+    // the first one in the order of the cells
+    fn pivot_with(&mut self, i: usize, j: usize, bland: bool) {
+        self.trans.set(i, j, Some(0));
+        let trans_state = self.cycle_detection(i, j);
+
+        let mut min = u64::MAX;
+        let mut f = true;
+        for x in trans_state.iter(i, j) {
+            if f {
+                f = false;
+            } else {
+                if min > self.trans.get(x.0, x.1).unwrap() {
+                    min = self.trans.get(x.0, x.1).unwrap();
                 }
+                f = true;
             }
+        }
 
-            f = true;
-            for x in trans_state.iter(i, j) {
-                if f {
-                    self.trans[x.0][x.1] = Some(self.trans[x.0][x.1].unwrap() + min);
-                    f = false;
-                } else {
-                    self.trans[x.0][x.1] = Some(self.trans[x.0][x.1].unwrap() - min);
-                    f = true;
-                }
+        f = true;
+        for x in trans_state.iter(i, j) {
+            if f {
+                let y = self.trans.get(x.0, x.1).unwrap();
+                self.trans.set(x.0, x.1, Some(y + min));
+                f = false;
+            } else {
+                let y = self.trans.get(x.0, x.1).unwrap();
+                self.trans.set(x.0, x.1, Some(y - min));
+                f = true;
             }
+        }
 
-            let mut max = (0, 0, 0);
-            for x in trans_state.iter(i, j) {
-                if self.trans[x.0][x.1].unwrap() == 0 && max.2 <= self.cost[x.0][x.1] {
-                    max = (x.0, x.1, self.cost[x.0][x.1]);
+        // Only the cells that the cycle has decreased may leave the basis
+        let mut max: Option<(usize, usize, u64)> = None;
+        f = true;
+        for x in trans_state.iter(i, j) {
+            if !f && self.trans.get(x.0, x.1) == Some(0) {
+                let better = match max {
+                    None => true,
+                    Some((k, l, _)) if bland => (x.0, x.1) < (k, l),
+                    Some((_, _, c)) => c <= self.cost_of(x.0, x.1),
+                };
+                if better {
+                    max = Some((x.0, x.1, self.cost_of(x.0, x.1)));
                 }
             }
-            self.trans[max.0][max.1] = None;
+            f = !f;
         }
+        let (k, l, _) = max.unwrap();
+        self.trans.set(k, l, None);
     }
 
     pub fn printstd(&self) {
-        let mut table = Table::new();
-        table.add_row(Row::new(vec![Cell::new("")]));
-        for i in 0..self.demand.len() {
-            table[0].add_cell(Cell::new(&format!("B{}", i + 1)));
-        }
-        table[0].add_cell(Cell::new("Запасы"));
-        for (i, s) in self.supply.iter().enumerate() {
-            table.add_row(Row::new(vec![Cell::new(&format!("A{}", i + 1))]));
-            for m in self.trans[i].iter() {
-                if m.is_none() {
-                    table[i + 1].add_cell(Cell::new("0"));
-                } else {
-                    table[i + 1].add_cell(Cell::new(&format!("{}", m.unwrap())));
-                }
-            }
-            table[i + 1].add_cell(Cell::new(&format!("{}", s)));
+        if let Some(ref ranges) = self.ranges {
+            return ranges.printstd(self);
         }
-        table.add_row(Row::new(vec![Cell::new("Потребности")]));
-        for d in self.demand.iter() {
-            table[self.supply.len() + 1].add_cell(Cell::new(&format!("{}", d)));
+        if let Some(ref tariffs) = self.tariffs {
+            return tariffs.printstd(self);
         }
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan(), &supply, &demand);
         table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
         table.printstd();
     }
 
+    // Solve the problem with the costs queried from the source when needed
+    pub fn with_source(mut a: Vec<u64>, mut b: Vec<u64>, c: C) -> Result<Transportation<C>, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if c.shape() != (a.len(), b.len()) {
+            return Err(if c.shape().0 != a.len() { NumOfRows } else { NumOfCols });
+        }
+
+        let shape = (a.len(), b.len());
+        let sum_s: u64 = a.iter().sum();
+        let sum_d: u64 = b.iter().sum();
+        if sum_s > sum_d {
+            b.push(sum_s - sum_d);
+        }
+        if sum_s < sum_d {
+            a.push(sum_d - sum_s);
+        }
+        Ok(Transportation {
+            trans: Basis::new(a.len(), b.len()),
+            shape,
+            supply: a,
+            demand: b,
+            cost: c,
+            ranges: None,
+            tariffs: None,
+            forbidden: Vec::new(),
+        })
+    }
+}
+
+impl Transportation {
     pub fn new(mut a: Vec<u64>, mut b: Vec<u64>, mut c: Vec<Vec<u64>>) -> Result<Transportation, Error> {
         if a.is_empty() || b.is_empty() {
             return Err(NumOfSupOrCust);
@@ -425,8 +672,9 @@ impl Transportation {
             }
         }
 
-        let sum_s = a.iter().fold(0, std::ops::Add::add); //a.iter().sum();
-        let sum_d = b.iter().fold(0, std::ops::Add::add); //b.iter().sum();
+        let shape = (a.len(), b.len());
+        let sum_s: u64 = a.iter().sum();
+        let sum_d: u64 = b.iter().sum();
         if sum_s > sum_d {
             b.push(sum_s - sum_d);
             for i in &mut c {
@@ -437,19 +685,91 @@ impl Transportation {
             a.push(sum_d - sum_s);
             c.push(vec![0;b.len()]);
         }
-        let mut cost = Matrix::new(c[0].len());
+        let mut t = Transportation::balanced(a, b, c);
+        t.shape = shape;
+        Ok(t)
+    }
+    // Build the structure from an already balanced problem
+    fn balanced(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>) -> Transportation {
+        let mut cost = Matrix::new(b.len());
         for i in c {
             cost.push(i);
         }
 
-        Ok(Transportation {
-            trans: Matrix {
-                cols: b.len(),
-                data: vec![None;a.len()*b.len()],
-            },
+        Transportation {
+            trans: Basis::new(a.len(), b.len()),
+            shape: (a.len(), b.len()),
             supply: a,
             demand: b,
-            cost: cost,
-        })
+            cost,
+            ranges: None,
+            tariffs: None,
+            forbidden: Vec::new(),
+        }
+    }
+}
+
+// The differences of the potentials of the current basis for the pricing rules
+struct BasisPrices<'a, C: 'a> {
+    t: &'a Transportation<C>,
+    u: Vec<i64>,
+    v: Vec<i64>,
+}
+
+impl<'a, C: CostSource> Prices for BasisPrices<'a, C> {
+    fn shape(&self) -> (usize, usize) {
+        (self.u.len(), self.v.len())
+    }
+
+    fn difference(&self, i: usize, j: usize) -> i64 {
+        self.t.cost_of(i, j) as i64 - self.u[i] - self.v[j]
+    }
+
+    fn cycle_len(&self, i: usize, j: usize) -> usize {
+        pricing::cycle_len(&|i, j| self.t.trans.get(i, j).is_some(), self.u.len(), self.v.len(), i, j)
+    }
+}
+
+// The table of the plan with the supplies in the last column and the demands in the last row
+fn plan_table<T: std::fmt::Display>(plan: &[Vec<T>], supply: &[String], demand: &[String]) -> Table {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![Cell::new("")]));
+    for i in 0..demand.len() {
+        table[0].add_cell(Cell::new(&format!("B{}", i + 1)));
     }
+    table[0].add_cell(Cell::new("Запасы"));
+    for (i, s) in supply.iter().enumerate() {
+        table.add_row(Row::new(vec![Cell::new(&format!("A{}", i + 1))]));
+        for x in plan[i].iter() {
+            table[i + 1].add_cell(Cell::new(&format!("{}", x)));
+        }
+        table[i + 1].add_cell(Cell::new(s));
+    }
+    table.add_row(Row::new(vec![Cell::new("Потребности")]));
+    for d in demand.iter() {
+        table[supply.len() + 1].add_cell(Cell::new(d));
+    }
+    table
+}
+
+// The label of the node or the prefix and its number if there is none
+fn label(labels: &[String], k: usize, prefix: &str) -> String {
+    labels.get(k).cloned().unwrap_or_else(|| format!("{}{}", prefix, k + 1))
+}
+
+// A cost that keeps the flow away from a cell whenever the problem allows it:
+// any plan that avoids such cells is cheaper than a single unit sent through one.
+// The potentials add up the costs along the basis, so the cost times the number of rows
+// and columns must fit in i64, Error::Overflow is returned otherwise.
+fn checked_prohibitive_cost(c: &[Vec<u64>], flow: u64) -> Result<u64, Error> {
+    let max = c.iter().flat_map(|row| row.iter()).cloned().max().unwrap_or(0);
+    prohibitive_cost_for(max, flow, c.len() + c.first().map_or(0, |row| row.len()))
+}
+// The same for the largest cost `max` of the problem with the given number of suppliers and customers
+fn prohibitive_cost_for(max: u64, flow: u64, nodes: usize) -> Result<u64, Error> {
+    let nodes = nodes as u64 + 2;
+    max.checked_mul(flow)
+       .and_then(|m| m.checked_add(1))
+       .filter(|m| m.checked_mul(nodes).is_some_and(|x| x <= i64::MAX as u64))
+       .ok_or(Overflow)
 }
@@ -0,0 +1,200 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{plan_table, Transportation};
+use super::Error::{self, *};
+
+// Costs with the opening cost spread over the capacity are kept in integers multiplied by this factor
+const SCALE: u64 = 1000;
+
+// Capacitated facility location: which depots to open, so that the opening cost
+// together with the cost of serving the customers from the open depots is minimal
+pub struct FacilityLocation {
+    capacity: Vec<u64>,
+    opening: Vec<u64>,
+    demand: Vec<u64>,
+    cost: Vec<Vec<u64>>,
+    open: Vec<bool>,
+    plan: Vec<Vec<u64>>,
+}
+
+impl FacilityLocation {
+    // Solve the transportation problem from the depots with the given capacities
+    // and drop the dummies from the plan
+    fn allocate(&self, capacity: Vec<u64>, c: Vec<Vec<u64>>) -> (Vec<Vec<u64>>, u64) {
+        let mut t = Transportation::new(capacity, self.demand.clone(), c).unwrap();
+        t.potential_method();
+        (t.original_plan(), t.total_cost())
+    }
+    // The plan and its total cost when only the given depots are open
+    fn evaluate(&self, open: &[bool]) -> Option<(Vec<Vec<u64>>, u64)> {
+        let capacity: Vec<u64> = self.capacity.iter().zip(open.iter()).map(|(&s, &o)| if o { s } else { 0 }).collect();
+        if capacity.iter().sum::<u64>() < self.demand.iter().sum() {
+            return None;
+        }
+        let (plan, z) = self.allocate(capacity, self.cost.clone());
+        let opening: u64 = self.opening.iter().zip(open.iter()).filter(|&(_, &o)| o).map(|(f, _)| f).sum();
+        Some((plan, z + opening))
+    }
+    // Keep the solution if it is cheaper than the current one
+    fn update(&mut self, open: Vec<bool>) -> bool {
+        if let Some((plan, z)) = self.evaluate(&open) {
+            if self.plan.is_empty() || z < self.total_cost() {
+                self.open = open;
+                self.plan = plan;
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn opening_cost(&self) -> u64 {
+        self.opening.iter().zip(self.open.iter()).filter(|&(_, &o)| o).map(|(f, _)| f).sum()
+    }
+
+    pub fn transport_cost(&self) -> u64 {
+        self.plan.iter().zip(self.cost.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum()
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.opening_cost() + self.transport_cost()
+    }
+    // The indices of the open depots
+    pub fn opened(&self) -> Vec<usize> {
+        (0..self.open.len()).filter(|&i| self.open[i]).collect()
+    }
+
+    pub fn plan(&self) -> &[Vec<u64>] {
+        &self.plan
+    }
+    // Start with all depots open and close the one that saves the most while it saves anything,
+    // then open the closed depots one by one while it reduces the total cost
+    pub fn heuristic(&mut self) -> Result<(), Error> {
+        self.plan = Vec::new();
+        if !self.update(vec![true; self.capacity.len()]) {
+            return Err(Infeasible);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            let mut best = None;
+            for i in self.opened() {
+                let mut open = self.open.clone();
+                open[i] = false;
+                if let Some((_, z)) = self.evaluate(&open) {
+                    if z < best.as_ref().map_or(self.total_cost(), |b: &(Vec<bool>, u64)| b.1) {
+                        best = Some((open, z));
+                    }
+                }
+            }
+            if let Some((open, _)) = best {
+                changed = self.update(open);
+            }
+            for i in 0..self.open.len() {
+                if !self.open[i] {
+                    let mut open = self.open.clone();
+                    open[i] = true;
+                    changed |= self.update(open);
+                }
+            }
+        }
+        Ok(())
+    }
+    // Exact solution for small problems. Every depot is either undecided, opened or closed.
+    // The lower bound of a node is given by the transportation problem where the opening cost
+    // of the undecided depots is spread over their capacity.
+    pub fn branch_and_bound(&mut self) -> Result<(), Error> {
+        self.heuristic()?;
+
+        let mut stack = vec![vec![None; self.capacity.len()]];
+        while let Some(state) = stack.pop() {
+            let capacity: Vec<u64> = self.capacity.iter().zip(state.iter()).map(|(&s, o)| if *o == Some(false) { 0 } else { s }).collect();
+            if capacity.iter().sum::<u64>() < self.demand.iter().sum() {
+                continue;
+            }
+            let mut opening = 0;
+            let mut c = Vec::new();
+            for (i, o) in state.iter().enumerate() {
+                let spread = match *o {
+                    None => self.opening[i] * SCALE / std::cmp::max(self.capacity[i], 1),
+                    Some(true) => {
+                        opening += self.opening[i];
+                        0
+                    }
+                    Some(false) => 0,
+                };
+                c.push(self.cost[i].iter().map(|c| c * SCALE + spread).collect());
+            }
+            let (plan, z) = self.allocate(capacity, c);
+            if opening + z.div_ceil(SCALE) >= self.total_cost() {
+                continue;
+            }
+            let open: Vec<bool> = state.iter().zip(plan.iter()).map(|(o, x)| *o == Some(true) || x.iter().any(|&x| x > 0)).collect();
+            self.update(open);
+
+            // Branch on the undecided depot whose opening cost is underestimated the most.
+            // A depot used at its capacity is still branched on if its spread cost was rounded.
+            let mut branch = None;
+            let mut gap = 0;
+            for (i, o) in state.iter().enumerate() {
+                let x: u64 = plan[i].iter().sum();
+                let s = self.capacity[i];
+                if o.is_none() && x > 0 && (x < s || !(self.opening[i] * SCALE).is_multiple_of(s)) {
+                    let g = self.opening[i] * (s - x) / s;
+                    if branch.is_none() || g > gap {
+                        branch = Some(i);
+                        gap = g;
+                    }
+                }
+            }
+            if let Some(i) = branch {
+                let mut closed = state.clone();
+                closed[i] = Some(false);
+                stack.push(closed);
+                let mut open = state;
+                open[i] = Some(true);
+                stack.push(open);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.capacity
+                                      .iter()
+                                      .zip(self.open.iter())
+                                      .map(|(s, &o)| if o { format!("{}", s) } else { "закрыт".to_owned() })
+                                      .collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan, &supply, &demand);
+        table.add_row(Row::new(vec![Cell::new("Открытие складов"), Cell::new(&format!("{}", self.opening_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Перевозка"), Cell::new(&format!("{}", self.transport_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.printstd();
+    }
+    // s is the capacity of the depots, f is their opening cost, b is the demand of the customers
+    // and c is the cost of sending a unit from a depot to a customer
+    pub fn new(s: Vec<u64>, f: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>) -> Result<FacilityLocation, Error> {
+        if s.is_empty() || b.is_empty() || s.len() != f.len() {
+            return Err(NumOfSupOrCust);
+        }
+        if s.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        Ok(FacilityLocation {
+            open: vec![false; s.len()],
+            capacity: s,
+            opening: f,
+            demand: b,
+            cost: c,
+            plan: Vec::new(),
+        })
+    }
+}
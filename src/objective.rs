@@ -0,0 +1,154 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{checked_prohibitive_cost, plan_table, Transportation};
+use super::Error::{self, *};
+
+// Transportation with several objectives, e.g. the cost, the time and the emissions.
+// Every objective is a matrix of the same shape as the matrix of costs.
+pub struct MultiObjective {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    objectives: Vec<Vec<Vec<u64>>>,
+}
+
+impl MultiObjective {
+    // Solve the problem with the combined costs, the forbidden cells of the balanced problem
+    // get the prohibitive cost. Return the solved problem.
+    fn solve(&self, c: Vec<Vec<u64>>, forbidden: &[bool]) -> Transportation {
+        // Only the single objectives forbid cells and new has checked their costs
+        let m = checked_prohibitive_cost(&c, self.supply.iter().sum()).unwrap_or(0);
+        let mut t = Transportation::new(self.supply.clone(), self.demand.clone(), c).unwrap();
+        for (c, &f) in t.cost.data.iter_mut().zip(forbidden.iter()) {
+            if f {
+                *c = m;
+            }
+        }
+        t.potential_method();
+        t
+    }
+    // The plan without the dummies
+    fn trim(&self, t: &Transportation) -> Vec<Vec<u64>> {
+        t.original_plan()
+    }
+    // The values of all objectives for the plan
+    pub fn values(&self, plan: &[Vec<u64>]) -> Vec<u64> {
+        self.objectives
+            .iter()
+            .map(|c| plan.iter().zip(c.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum())
+            .collect()
+    }
+    // Minimize the sum of the objectives multiplied by the weights
+    // Return Error::Overflow if the combined costs don't fit
+    pub fn weighted_sum(&self, w: &[u64]) -> Result<Vec<Vec<u64>>, Error> {
+        assert_eq!(w.len(), self.objectives.len());
+        let mut c = vec![vec![0u64; self.demand.len()]; self.supply.len()];
+        for (i, row) in c.iter_mut().enumerate() {
+            for (j, c) in row.iter_mut().enumerate() {
+                for (o, &w) in self.objectives.iter().zip(w.iter()) {
+                    *c = o[i][j].checked_mul(w).and_then(|x| x.checked_add(*c)).ok_or(Overflow)?;
+                }
+            }
+        }
+        // The potentials add up the combined costs as well
+        checked_prohibitive_cost(&c, 1)?;
+        let t = self.solve(c, &[]);
+        Ok(self.trim(&t))
+    }
+    // Minimize the objectives one by one in the given order, every next one only among
+    // the plans that are optimal for the previous ones. By the complementary slackness
+    // these plans don't use the cells with a positive difference of the potentials.
+    pub fn lexicographic(&self, order: &[usize]) -> Vec<Vec<u64>> {
+        let mut forbidden: Vec<bool> = Vec::new();
+        let mut plan = Vec::new();
+        for &k in order {
+            let t = self.solve(self.objectives[k].clone(), &forbidden);
+            let (u, v) = t.calculation_of_potentials();
+            forbidden = (0..t.cost.data.len())
+                            .map(|n| {
+                                let (i, j) = (n / t.cost.cols(), n % t.cost.cols());
+                                forbidden.get(n).cloned().unwrap_or(false) || t.cost[i][j] as i64 - u[i] - v[j] > 0
+                            })
+                            .collect();
+            plan = self.trim(&t);
+        }
+        plan
+    }
+    // Pareto-efficient plans found by varying the weights: every weight is a positive part
+    // of the given number of steps. The lexicographic optimum of every objective is added,
+    // the dominated and the repeated plans are dropped.
+    pub fn pareto(&self, steps: u64) -> Result<Vec<Vec<Vec<u64>>>, Error> {
+        let n = self.objectives.len();
+        let mut plans = Vec::new();
+        for k in 0..n {
+            let order: Vec<usize> = (k..n).chain(0..k).collect();
+            plans.push(self.lexicographic(&order));
+        }
+
+        for w in compositions(steps, n) {
+            plans.push(self.weighted_sum(&w)?);
+        }
+
+        let values: Vec<Vec<u64>> = plans.iter().map(|p| self.values(p)).collect();
+        let dominated = |a: &[u64], b: &[u64]| a.iter().zip(b.iter()).all(|(x, y)| y <= x) && a != b;
+        let mut result: Vec<(Vec<u64>, Vec<Vec<u64>>)> = Vec::new();
+        for (p, z) in plans.into_iter().zip(values.iter()) {
+            if !values.iter().any(|y| dominated(z, y)) && !result.iter().any(|r| r.0 == *z) {
+                result.push((z.clone(), p));
+            }
+        }
+        result.sort();
+        Ok(result.into_iter().map(|r| r.1).collect())
+    }
+    // One table per plan with the values of all objectives
+    pub fn printstd(&self, plans: &[Vec<Vec<u64>>]) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        for plan in plans {
+            let mut table = plan_table(plan, &supply, &demand);
+            for (k, z) in self.values(plan).iter().enumerate() {
+                table.add_row(Row::new(vec![Cell::new(&format!("Критерий {}", k + 1)), Cell::new(&format!("{}", z))]));
+            }
+            table.printstd();
+        }
+    }
+    // a is the supply, b is the demand and c[k] is the matrix of the objective k
+    pub fn new(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<Vec<u64>>>) -> Result<MultiObjective, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if c.is_empty() {
+            return Err(NumOfObjectives);
+        }
+        if c.iter().any(|c| c.len() != a.len()) {
+            return Err(NumOfRows);
+        }
+        if c.iter().flat_map(|c| c.iter()).any(|i| i.len() != b.len()) {
+            return Err(NumOfCols);
+        }
+        for c in &c {
+            checked_prohibitive_cost(c, a.iter().sum())?;
+        }
+
+        Ok(MultiObjective {
+            supply: a,
+            demand: b,
+            objectives: c,
+        })
+    }
+}
+
+// All ways to split the total into the given number of positive parts
+fn compositions(total: u64, parts: usize) -> Vec<Vec<u64>> {
+    if parts == 1 {
+        return if total > 0 { vec![vec![total]] } else { Vec::new() };
+    }
+    let mut result = Vec::new();
+    for first in 1..total {
+        for mut rest in compositions(total - first, parts - 1) {
+            rest.insert(0, first);
+            result.push(rest);
+        }
+    }
+    result
+}
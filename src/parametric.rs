@@ -0,0 +1,152 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{checked_prohibitive_cost, plan_table, Matrix, Transportation};
+use super::Error::{self, *};
+
+// The range of the parameter [from, to] and the plan that is optimal in it
+pub type Interval = (f64, f64, Vec<Vec<u64>>);
+
+// A value of the parameter as the fraction numerator / denominator
+pub type Fraction = (u64, u64);
+
+// Parametric analysis of the costs c + t*d, e.g. a surcharge that scales some of the costs.
+//
+// The basis optimal for some t stays optimal while the differences of the potentials
+// c_ij - u_i - v_j + t*(d_ij - u'_i - v'_j) are not negative. They are linear in t,
+// so the next breakpoint is the smallest t where one of them turns negative. There the cell
+// enters the basis and the walk goes on from the new basis.
+pub struct Parametric {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    base: Vec<Vec<u64>>,
+    slope: Vec<Vec<u64>>,
+    intervals: Vec<Interval>,
+}
+
+impl Parametric {
+    // The differences of the potentials of all cells for the base costs and for the slope
+    fn differences(t: &Transportation, d: &Matrix<u64>) -> Vec<(usize, usize, i128, i128)> {
+        let (u, v) = t.potentials_for(|i, j| t.cost[i][j]);
+        let (u2, v2) = t.potentials_for(|i, j| d[i][j]);
+        let mut result = Vec::new();
+        for i in 0..t.cost.rows() {
+            for j in 0..t.cost.cols() {
+                let dc = t.cost[i][j] as i128 - u[i] as i128 - v[j] as i128;
+                let dd = d[i][j] as i128 - u2[i] as i128 - v2[j] as i128;
+                result.push((i, j, dc, dd));
+            }
+        }
+        result
+    }
+    // Make the basis optimal just to the right of t = p / q: the cells are compared
+    // by the difference at t and then by its slope. The bases are often degenerate,
+    // so Bland's rule picks the first cell that improves and the first one that leaves.
+    fn reoptimize(t: &mut Transportation, d: &Matrix<u64>, p: i128, q: i128) {
+        loop {
+            let entering = Parametric::differences(t, d).into_iter().find(|&(_, _, dc, dd)| {
+                let at = dc * q + dd * p;
+                at < 0 || at == 0 && dd < 0
+            });
+            match entering {
+                Some((i, j, _, _)) => t.pivot_with(i, j, true),
+                None => return,
+            }
+        }
+    }
+    // Walk the breakpoints of t from `from` to `to`. Return Error::Parameter if a denominator
+    // is zero or `from` is greater than `to`.
+    pub fn analyze(&mut self, from: Fraction, to: Fraction) -> Result<(), Error> {
+        if from.1 == 0 || to.1 == 0 || from.0 as u128 * to.1 as u128 > to.0 as u128 * from.1 as u128 {
+            return Err(Parameter);
+        }
+        self.intervals = Vec::new();
+        // The costs at `from` times its denominator have the same optimal plans
+        let mut c = vec![vec![0; self.demand.len()]; self.supply.len()];
+        for (i, row) in c.iter_mut().enumerate() {
+            for (j, c) in row.iter_mut().enumerate() {
+                let base = self.base[i][j].checked_mul(from.1);
+                let slope = self.slope[i][j].checked_mul(from.0);
+                *c = base.and_then(|x| slope.and_then(|y| x.checked_add(y))).ok_or(Overflow)?;
+            }
+        }
+        checked_prohibitive_cost(&c, 1)?;
+        let mut t = Transportation::new(self.supply.clone(), self.demand.clone(), c)?;
+        t.potential_method();
+
+        // The dummies have zero costs in both matrices
+        let mut d = Matrix::new(t.cost.cols());
+        for i in 0..t.cost.rows() {
+            d.push((0..t.cost.cols()).map(|j| if i < self.supply.len() && j < self.demand.len() { self.slope[i][j] } else { 0 }).collect());
+            for j in 0..self.demand.len() {
+                if i < self.supply.len() {
+                    t.cost[i][j] = self.base[i][j];
+                }
+            }
+        }
+
+        // The parameter is kept as the fraction p / q
+        let (mut p, mut q) = (from.0 as i128, from.1 as i128);
+        loop {
+            Parametric::reoptimize(&mut t, &d, p, q);
+            let mut next = (to.0 as i128, to.1 as i128);
+            for (_, _, dc, dd) in Parametric::differences(&t, &d) {
+                if dd < 0 && dc * next.1 < -dd * next.0 {
+                    next = (dc, -dd);
+                }
+            }
+            let plan = t.original_plan();
+            let end = next.0 as f64 / next.1 as f64;
+            match self.intervals.last_mut() {
+                Some(last) if last.2 == plan => last.1 = end,
+                _ => self.intervals.push((p as f64 / q as f64, end, plan)),
+            }
+            if next.0 * to.1 as i128 >= to.0 as i128 * next.1 {
+                return Ok(());
+            }
+            p = next.0;
+            q = next.1;
+        }
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+    // The cost of the plan is base + t * slope
+    pub fn cost(&self, plan: &[Vec<u64>]) -> (u64, u64) {
+        let sum = |c: &[Vec<u64>]| plan.iter().zip(c.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum();
+        (sum(&self.base), sum(&self.slope))
+    }
+    // One table per interval
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        for &(from, to, ref plan) in &self.intervals {
+            println!("t от {} до {}", from, to);
+            let (base, slope) = self.cost(plan);
+            let mut table = plan_table(plan, &supply, &demand);
+            table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{} + {}t", base, slope))]));
+            table.printstd();
+        }
+    }
+    // a is the supply, b is the demand, c is the cost at t = 0 and d is its growth per unit of t
+    pub fn new(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>, d: Vec<Vec<u64>>) -> Result<Parametric, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() || a.len() != d.len() {
+            return Err(NumOfRows);
+        }
+        if c.iter().chain(d.iter()).any(|i| i.len() != b.len()) {
+            return Err(NumOfCols);
+        }
+
+        Ok(Parametric {
+            supply: a,
+            demand: b,
+            base: c,
+            slope: d,
+            intervals: Vec::new(),
+        })
+    }
+}
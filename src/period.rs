@@ -0,0 +1,199 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+use prettytable::Table;
+
+use super::{checked_prohibitive_cost, plan_table, Transportation};
+use super::Error::{self, *};
+
+// Transportation planned over several periods. The suppliers may keep the stock
+// for the next periods and the customers may accept late deliveries if they have
+// a penalty for the delay.
+//
+// It is expanded into a single transportation problem: every pair of a supplier and
+// a period is a row, every pair of a customer and a period is a column. The stock of
+// the period s sent to the demand of the period t is held until t at the supplier
+// (s <= t) or delivered late in s (s > t). The extra row covers the demand that can't
+// be met and the extra column takes the stock that is left.
+pub struct MultiPeriod {
+    supply: Vec<Vec<u64>>,
+    demand: Vec<Vec<u64>>,
+    cost: Vec<Vec<u64>>,
+    holding: Vec<u64>,
+    backorder: Option<Vec<u64>>,
+    inner: Transportation,
+}
+
+impl MultiPeriod {
+    fn periods(&self) -> usize {
+        self.supply.len()
+    }
+    // The amount of the stock of the period s sent by the supplier i to the demand of the customer j in the period t
+    fn amount(&self, s: usize, i: usize, t: usize, j: usize) -> u64 {
+        let rows = self.supply[0].len();
+        let cols = self.demand[0].len();
+        self.inner.trans.get(s * rows + i, t * cols + j).unwrap_or(0)
+    }
+    // Iterate over all amounts as (s, i, t, j, amount)
+    fn amounts<'a>(&'a self) -> Box<dyn Iterator<Item = (usize, usize, usize, usize, u64)> + 'a> {
+        let rows = self.supply[0].len();
+        let cols = self.demand[0].len();
+        let periods = self.periods();
+        Box::new((0..periods * rows).flat_map(move |r| {
+            (0..periods * cols).map(move |c| (r / rows, r % rows, c / cols, c % cols, self.amount(r / rows, r % rows, c / cols, c % cols)))
+        }))
+    }
+    // The shipments made in the period, the late deliveries included
+    pub fn shipments(&self, period: usize) -> Vec<Vec<u64>> {
+        let mut plan = vec![vec![0; self.demand[0].len()]; self.supply[0].len()];
+        for (s, i, t, j, x) in self.amounts() {
+            if std::cmp::max(s, t) == period {
+                plan[i][j] += x;
+            }
+        }
+        plan
+    }
+    // The stock kept by the suppliers at the end of the period for the next ones
+    pub fn inventory(&self, period: usize) -> Vec<u64> {
+        let mut stock = vec![0; self.supply[0].len()];
+        for (s, i, t, _, x) in self.amounts() {
+            if s <= period && period < t {
+                stock[i] += x;
+            }
+        }
+        stock
+    }
+    // The demand of the customers that is still not delivered at the end of the period
+    pub fn backorders(&self, period: usize) -> Vec<u64> {
+        let mut late = vec![0; self.demand[0].len()];
+        for (s, _, t, j, x) in self.amounts() {
+            if t <= period && period < s {
+                late[j] += x;
+            }
+        }
+        late
+    }
+
+    pub fn transport_cost(&self) -> u64 {
+        self.amounts().map(|(_, i, _, j, x)| x * self.cost[i][j]).sum()
+    }
+
+    pub fn holding_cost(&self) -> u64 {
+        self.amounts().filter(|&(s, _, t, _, _)| s < t).map(|(s, i, t, _, x)| x * self.holding[i] * (t - s) as u64).sum()
+    }
+
+    pub fn backorder_cost(&self) -> u64 {
+        match self.backorder {
+            Some(ref p) => self.amounts().filter(|&(s, _, t, _, _)| s > t).map(|(s, _, t, j, x)| x * p[j] * (s - t) as u64).sum(),
+            None => 0,
+        }
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.transport_cost() + self.holding_cost() + self.backorder_cost()
+    }
+    // Return Error::Infeasible if the demand can't be met
+    pub fn solve(&mut self) -> Result<(), Error> {
+        self.inner.potential_method();
+        // The prohibitive cost is used by the shortage and the forbidden late deliveries only
+        let m = self.inner.cost.data.iter().cloned().max().unwrap_or(0);
+        let used = self.inner.trans.cells().any(|(i, j, x)| x > 0 && self.inner.cost[i][j] == m);
+        if used {
+            return Err(Infeasible);
+        }
+        Ok(())
+    }
+    // One table per period
+    pub fn printstd(&self) {
+        for period in 0..self.periods() {
+            println!("Период {}", period + 1);
+            let supply: Vec<String> = self.supply[period].iter().map(u64::to_string).collect();
+            let demand: Vec<String> = self.demand[period].iter().map(u64::to_string).collect();
+            let mut table = plan_table(&self.shipments(period), &supply, &demand);
+            table[0].add_cell(Cell::new("Хранится"));
+            for (i, x) in self.inventory(period).iter().enumerate() {
+                table[i + 1].add_cell(Cell::new(&format!("{}", x)));
+            }
+            let mut row = Row::new(vec![Cell::new("Задолженность")]);
+            for x in self.backorders(period) {
+                row.add_cell(Cell::new(&format!("{}", x)));
+            }
+            table.add_row(row);
+            table.printstd();
+        }
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Перевозка"), Cell::new(&format!("{}", self.transport_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Хранение"), Cell::new(&format!("{}", self.holding_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Штрафы за задержку"), Cell::new(&format!("{}", self.backorder_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.printstd();
+    }
+    // a[t] and b[t] are the supply and the demand of the period t, h[i] is the cost of keeping
+    // a unit at the supplier i for a period, p[j] is the penalty for delivering a unit to the customer j
+    // one period late. Late deliveries are forbidden without the penalties.
+    pub fn new(a: Vec<Vec<u64>>, b: Vec<Vec<u64>>, c: Vec<Vec<u64>>, h: Vec<u64>, p: Option<Vec<u64>>) -> Result<MultiPeriod, Error> {
+        if a.is_empty() || a.len() != b.len() {
+            return Err(NumOfPeriods);
+        }
+        if a[0].is_empty() || b[0].is_empty() || a.iter().any(|v| v.len() != a[0].len()) || b.iter().any(|v| v.len() != b[0].len()) ||
+           h.len() != a[0].len() || p.as_ref().is_some_and(|p| p.len() != b[0].len()) {
+            return Err(NumOfSupOrCust);
+        }
+        if a[0].len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b[0].len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        let periods = a.len();
+        let rows = a[0].len();
+        let cols = b[0].len();
+        let sum_s: u64 = a.iter().flat_map(|v| v.iter()).sum();
+        let sum_d: u64 = b.iter().flat_map(|v| v.iter()).sum();
+
+        let mut cost = Vec::new();
+        for s in 0..periods {
+            for i in 0..rows {
+                let mut row = Vec::new();
+                for t in 0..periods {
+                    for j in 0..cols {
+                        row.push(if s <= t {
+                            Some(c[i][j] + h[i] * (t - s) as u64)
+                        } else {
+                            p.as_ref().map(|p| c[i][j] + p[j] * (s - t) as u64)
+                        });
+                    }
+                }
+                cost.push(row);
+            }
+        }
+        let known: Vec<Vec<u64>> = cost.iter().map(|row| row.iter().map(|c| c.unwrap_or(0)).collect()).collect();
+        let m = checked_prohibitive_cost(&known, sum_s)?;
+        let mut cost: Vec<Vec<u64>> = cost.into_iter()
+                                          .map(|row| {
+                                              let mut row: Vec<u64> = row.into_iter().map(|c| c.unwrap_or(m)).collect();
+                                              row.push(0);
+                                              row
+                                          })
+                                          .collect();
+        let mut shortage = vec![m; periods * cols];
+        shortage.push(0);
+        cost.push(shortage);
+
+        let mut supply: Vec<u64> = a.iter().flat_map(|v| v.iter().cloned()).collect();
+        supply.push(sum_d);
+        let mut demand: Vec<u64> = b.iter().flat_map(|v| v.iter().cloned()).collect();
+        demand.push(sum_s);
+
+        Ok(MultiPeriod {
+            inner: Transportation::balanced(supply, demand, cost),
+            supply: a,
+            demand: b,
+            cost: c,
+            holding: h,
+            backorder: p,
+        })
+    }
+}
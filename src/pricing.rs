@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+// The differences of the potentials c_ij - u_i - v_j of the current basis
+pub trait Prices {
+    // The number of rows and columns, the dummies included
+    fn shape(&self) -> (usize, usize);
+    fn difference(&self, i: usize, j: usize) -> i64;
+    // The number of cells in the cycle that the free cell would make with the basis
+    fn cycle_len(&self, i: usize, j: usize) -> usize;
+}
+
+// The rule that chooses the cell entering the basis, None if the basis is optimal
+pub trait Pricing {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)>;
+}
+
+// The cells with the most negative differences, the most negative first.
+// The cells with equal differences keep their order.
+fn most_negative(prices: &dyn Prices, cells: &mut dyn Iterator<Item = (usize, usize)>, count: usize) -> Vec<(usize, usize, i64)> {
+    let mut best: Vec<(usize, usize, i64)> = Vec::new();
+    for (i, j) in cells {
+        let d = prices.difference(i, j);
+        if d < 0 && (best.len() < count || d < best[best.len() - 1].2) {
+            let k = best.iter().position(|b| b.2 > d).unwrap_or(best.len());
+            best.insert(k, (i, j, d));
+            best.truncate(count);
+        }
+    }
+    best
+}
+
+fn all_cells(prices: &dyn Prices) -> Box<dyn Iterator<Item = (usize, usize)>> {
+    let (rows, cols) = prices.shape();
+    Box::new((0..rows * cols).map(move |n| (n / cols, n % cols)))
+}
+
+// The most negative difference over all cells, the rule of Transportation::potential_method
+pub struct Dantzig;
+
+impl Pricing for Dantzig {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)> {
+        most_negative(prices, &mut *all_cells(prices), 1).first().map(|c| (c.0, c.1))
+    }
+}
+
+// The number of cells that is worth a thread of the parallel pricing,
+// a smaller scan is faster than starting the thread
+const CELLS_PER_THREAD: usize = 1 << 15;
+
+// The rule of Dantzig with the rows split between the threads. Every thread takes the first
+// of the most negative cells in its rows and the first of the most negative results is chosen,
+// so the cell is the same as with a single thread. Small problems are scanned without threads.
+// The threads share the prices, so the rule is used by Transportation::potential_method_parallel
+// instead of being a Pricing.
+pub struct Parallel {
+    threads: usize,
+}
+
+impl Parallel {
+    pub fn new(threads: usize) -> Parallel {
+        Parallel { threads: std::cmp::max(threads, 1) }
+    }
+
+    pub fn entering(&mut self, prices: &(dyn Prices + Sync)) -> Option<(usize, usize)> {
+        let (rows, cols) = prices.shape();
+        let threads = std::cmp::min(self.threads, rows * cols / CELLS_PER_THREAD);
+        if threads <= 1 {
+            return Dantzig.entering(prices);
+        }
+        let chunk = rows.div_ceil(threads).max(1);
+        let results: Vec<Option<(usize, usize, i64)>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..rows).step_by(chunk)
+                                           .map(|start| {
+                                               let end = std::cmp::min(start + chunk, rows);
+                                               scope.spawn(move || {
+                                                   let mut cells = (start * cols..end * cols).map(|c| (c / cols, c % cols));
+                                                   most_negative(prices, &mut cells, 1).first().cloned()
+                                               })
+                                           })
+                                           .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let mut best: Option<(usize, usize, i64)> = None;
+        for r in results.into_iter().flatten() {
+            if best.is_none_or(|b| r.2 < b.2) {
+                best = Some(r);
+            }
+        }
+        best.map(|b| (b.0, b.1))
+    }
+}
+
+// The first negative difference, the scan goes on from the last entering cell
+#[derive(Default)]
+pub struct FirstNegative {
+    next: usize,
+}
+
+impl Pricing for FirstNegative {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)> {
+        let (rows, cols) = prices.shape();
+        let n = rows * cols;
+        for k in 0..n {
+            let cell = (self.next + k) % n;
+            if prices.difference(cell / cols, cell % cols) < 0 {
+                self.next = cell + 1;
+                return Some((cell / cols, cell % cols));
+            }
+        }
+        None
+    }
+}
+
+// Partial pricing: a full scan keeps the list of the most negative cells,
+// then the cells of the list are priced again until none of them is negative
+pub struct CandidateList {
+    size: usize,
+    list: Vec<(usize, usize)>,
+}
+
+impl CandidateList {
+    pub fn new(size: usize) -> CandidateList {
+        CandidateList {
+            size: std::cmp::max(size, 1),
+            list: Vec::new(),
+        }
+    }
+}
+
+impl Pricing for CandidateList {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)> {
+        let mut best = most_negative(prices, &mut self.list.iter().cloned(), self.size);
+        if best.is_empty() {
+            best = most_negative(prices, &mut *all_cells(prices), self.size);
+        }
+        self.list = best.iter().skip(1).map(|c| (c.0, c.1)).collect();
+        best.first().map(|c| (c.0, c.1))
+    }
+}
+
+// Block pricing: the cells are split into blocks of the given size in the order of the rows,
+// the most negative cell of the first block that has one enters. The next search starts
+// from the block after it.
+pub struct Block {
+    size: usize,
+    start: usize,
+}
+
+impl Block {
+    pub fn new(size: usize) -> Block {
+        Block {
+            size: std::cmp::max(size, 1),
+            start: 0,
+        }
+    }
+}
+
+impl Pricing for Block {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)> {
+        let (rows, cols) = prices.shape();
+        let n = rows * cols;
+        let blocks = n.div_ceil(self.size);
+        for k in 0..blocks {
+            let block = (self.start + k) % blocks;
+            let end = std::cmp::min((block + 1) * self.size, n);
+            let best = most_negative(prices, &mut (block * self.size..end).map(|c| (c / cols, c % cols)), 1);
+            if let Some(c) = best.first() {
+                self.start = block + 1;
+                return Some((c.0, c.1));
+            }
+        }
+        None
+    }
+}
+
+// Approximate steepest edge: among the most negative cells the one with the largest
+// difference per the length of its cycle, i.e. d^2 / len, enters
+pub struct SteepestEdge {
+    candidates: usize,
+}
+
+impl SteepestEdge {
+    pub fn new(candidates: usize) -> SteepestEdge {
+        SteepestEdge { candidates: std::cmp::max(candidates, 1) }
+    }
+}
+
+impl Pricing for SteepestEdge {
+    fn entering(&mut self, prices: &dyn Prices) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize, i128, i128)> = None;
+        for (i, j, d) in most_negative(prices, &mut *all_cells(prices), self.candidates) {
+            let (d, len) = (d as i128 * d as i128, prices.cycle_len(i, j) as i128);
+            if best.is_none_or(|b| d * b.3 > b.2 * len) {
+                best = Some((i, j, d, len));
+            }
+        }
+        best.map(|b| (b.0, b.1))
+    }
+}
+
+// The length of the path between the row and the column in the basis, plus the free cell
+pub fn cycle_len(basic: &dyn Fn(usize, usize) -> bool, rows: usize, cols: usize, i: usize, j: usize) -> usize {
+    // The rows are the nodes 0..rows, the columns follow them
+    let mut depth = vec![None; rows + cols];
+    let mut queue = VecDeque::new();
+    depth[i] = Some(0);
+    queue.push_back(i);
+    while let Some(node) = queue.pop_front() {
+        let next: Vec<usize> = if node < rows {
+            (0..cols).filter(|&k| basic(node, k)).map(|k| rows + k).collect()
+        } else {
+            (0..rows).filter(|&k| basic(k, node - rows)).collect()
+        };
+        for k in next {
+            if depth[k].is_none() {
+                depth[k] = Some(depth[node].unwrap() + 1);
+                queue.push_back(k);
+            }
+        }
+    }
+    depth[rows + j].map_or(0, |d| d + 1)
+}
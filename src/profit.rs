@@ -0,0 +1,111 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{plan_table, Transportation};
+use super::Error::{self, *};
+
+// The suppliers produce at a cost up to their capacity, the customers buy at a price
+// up to their demand. The plan maximizes the profit, so the unprofitable customers
+// may be left without goods and the suppliers may keep a part of their capacity unused.
+//
+// The extra supplier has the whole demand and stands for the goods that are not sold.
+// The dummy customer added by Transportation::new takes the capacity that is not used.
+// The cost of a route is the largest profit of a unit minus the profit of the route,
+// both extras have zero profit. The amount sent in the balanced problem is fixed,
+// so the least cost gives the largest profit.
+pub struct Profit {
+    capacity: Vec<u64>,
+    production: Vec<u64>,
+    demand: Vec<u64>,
+    price: Vec<u64>,
+    cost: Vec<Vec<u64>>,
+    inner: Transportation,
+}
+
+impl Profit {
+    pub fn solve(&mut self) {
+        self.inner.potential_method();
+    }
+    // The plan without the extra supplier of the unmet demand
+    pub fn plan(&self) -> Vec<Vec<u64>> {
+        let mut plan = self.inner.original_plan();
+        plan.truncate(self.capacity.len());
+        plan
+    }
+    // The amount produced by every supplier
+    pub fn produced(&self) -> Vec<u64> {
+        self.plan().iter().map(|row| row.iter().sum()).collect()
+    }
+    // The amount sold to every customer
+    pub fn sold(&self) -> Vec<u64> {
+        let plan = self.plan();
+        (0..self.demand.len()).map(|j| plan.iter().map(|row| row[j]).sum()).collect()
+    }
+
+    pub fn revenue(&self) -> u64 {
+        self.sold().iter().zip(self.price.iter()).map(|(x, r)| x * r).sum()
+    }
+
+    pub fn production_cost(&self) -> u64 {
+        self.produced().iter().zip(self.production.iter()).map(|(x, p)| x * p).sum()
+    }
+
+    pub fn transport_cost(&self) -> u64 {
+        self.plan().iter().zip(self.cost.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum()
+    }
+
+    pub fn profit(&self) -> i64 {
+        self.revenue() as i64 - self.production_cost() as i64 - self.transport_cost() as i64
+    }
+
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.produced().iter().zip(self.capacity.iter()).map(|(x, a)| format!("{} ({})", x, a)).collect();
+        let demand: Vec<String> = self.sold().iter().zip(self.demand.iter()).map(|(x, b)| format!("{} ({})", x, b)).collect();
+        let mut table = plan_table(&self.plan(), &supply, &demand);
+        table.add_row(Row::new(vec![Cell::new("Выручка"), Cell::new(&format!("{}", self.revenue()))]));
+        table.add_row(Row::new(vec![Cell::new("Производство"), Cell::new(&format!("{}", self.production_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Перевозка"), Cell::new(&format!("{}", self.transport_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Прибыль"), Cell::new(&format!("{}", self.profit()))]));
+        table.printstd();
+    }
+    // a is the capacity of the suppliers and p is their cost of a unit, b is the largest demand
+    // of the customers and r is their price of a unit, c is the cost of sending a unit
+    pub fn new(a: Vec<u64>, p: Vec<u64>, b: Vec<u64>, r: Vec<u64>, c: Vec<Vec<u64>>) -> Result<Profit, Error> {
+        if a.is_empty() || b.is_empty() || a.len() != p.len() || b.len() != r.len() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        let profit = |i: usize, j: usize| r[j] as i64 - p[i] as i64 - c[i][j] as i64;
+        let max = (0..a.len()).flat_map(|i| (0..b.len()).map(move |j| (i, j))).map(|(i, j)| profit(i, j)).max().unwrap();
+        let max = std::cmp::max(max, 0);
+
+        let mut cost: Vec<Vec<u64>> = (0..a.len()).map(|i| (0..b.len()).map(|j| (max - profit(i, j)) as u64).collect()).collect();
+        cost.push(vec![max as u64; b.len()]);
+        let mut supply = a.clone();
+        supply.push(b.iter().sum());
+
+        let mut inner = Transportation::new(supply, b.clone(), cost)?;
+        if inner.cost.cols() > b.len() {
+            for i in 0..inner.cost.rows() {
+                inner.cost[i][b.len()] = max as u64;
+            }
+        }
+
+        Ok(Profit {
+            capacity: a,
+            production: p,
+            demand: b,
+            price: r,
+            cost: c,
+            inner,
+        })
+    }
+}
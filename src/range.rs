@@ -0,0 +1,181 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{checked_prohibitive_cost, label, plan_table, CostSource, Relation, Transportation};
+use super::Error::{self, *};
+
+// The relation of the row of a supplier or a customer and its right-hand side
+pub type Amount = (Relation, u64);
+
+// Supply and demand ranges of the original problem and the node behind every
+// row and column of the equivalent balanced problem (None for the dummies)
+pub struct Ranges {
+    supply: Vec<(u64, u64)>,
+    demand: Vec<(u64, u64)>,
+    cost: Vec<Vec<u64>>,
+    rows: Vec<Option<usize>>,
+    cols: Vec<Option<usize>>,
+}
+
+impl Ranges {
+    // Sum the plan of the balanced problem over the original suppliers and customers
+    pub fn plan<C: CostSource>(&self, t: &Transportation<C>) -> Vec<Vec<u64>> {
+        let mut plan = vec![vec![0; self.demand.len()]; self.supply.len()];
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, col) in self.cols.iter().enumerate() {
+                if let (Some(r), Some(c), Some(x)) = (*row, *col, t.trans.get(i, j)) {
+                    plan[r][c] += x;
+                }
+            }
+        }
+        plan
+    }
+
+    pub fn printstd<C: CostSource>(&self, t: &Transportation<C>) {
+        let plan = self.plan(t);
+        let supply: Vec<String> = self.supply
+                                      .iter()
+                                      .zip(plan.iter())
+                                      .map(|(&(min, max), row)| format!("{} ({}..{})", row.iter().sum::<u64>(), min, max))
+                                      .collect();
+        let demand: Vec<String> = self.demand
+                                      .iter()
+                                      .enumerate()
+                                      .map(|(j, &(min, max))| format!("{} ({}..{})", plan.iter().map(|row| row[j]).sum::<u64>(), min, max))
+                                      .collect();
+        let mut table = plan_table(&plan, &supply, &demand);
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", t.total_cost()))]));
+        table.printstd();
+    }
+    // The names of the rows and the columns of the balanced problem, the parts of a node share its name
+    pub fn names(&self, suppliers: &[String], customers: &[String]) -> (Vec<String>, Vec<String>) {
+        let name = |node: &Option<usize>, labels: &[String], prefix: &str| node.map_or("dummy".to_string(), |k| label(labels, k, prefix));
+        (self.rows.iter().map(|i| name(i, suppliers, "A")).collect(), self.cols.iter().map(|j| name(j, customers, "B")).collect())
+    }
+    // The cells of the dummies that the mandatory parts can't use
+    pub fn forbidden<C: CostSource>(&self, t: &Transportation<C>) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, col) in self.cols.iter().enumerate() {
+                if row.is_none() != col.is_none() && t.cost_of(i, j) > 0 {
+                    cells.push((i, j));
+                }
+            }
+        }
+        cells
+    }
+    // The rows of the original problem if every range is a single relation: a fixed amount
+    // is an equality, a range from zero is a <= row and a range whose maximum is more than
+    // the other side can take is a >= row
+    pub fn relations(&self) -> Option<(Vec<Amount>, Vec<Amount>)> {
+        fn relations(nodes: &[(u64, u64)], other: &[(u64, u64)]) -> Option<Vec<Amount>> {
+            let most: u64 = other.iter().map(|&(_, max)| max).sum();
+            nodes.iter()
+                 .map(|&(min, max)| match (min, max) {
+                     _ if min == max => Some((Relation::Equal, min)),
+                     (0, _) => Some((Relation::LessOrEqual, max)),
+                     _ if max >= most => Some((Relation::GreaterOrEqual, min)),
+                     _ => None,
+                 })
+                 .collect()
+        }
+        Some((relations(&self.supply, &self.demand)?, relations(&self.demand, &self.supply)?))
+    }
+    // The costs of the original problem
+    pub fn costs(&self) -> &[Vec<u64>] {
+        &self.cost
+    }
+    // The cells of the balanced problem that belong to the routes of the original problem
+    pub fn cells(&self, routes: &[(usize, usize)]) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, col) in self.cols.iter().enumerate() {
+                if let (Some(r), Some(c)) = (*row, *col) {
+                    if routes.contains(&(r, c)) {
+                        cells.push((i, j));
+                    }
+                }
+            }
+        }
+        cells
+    }
+}
+
+impl Transportation {
+    // Each supplier and customer is given by the range (min, max) of its amount.
+    // Every node is split into a mandatory part (min) and an optional part (max - min).
+    // The dummy customer takes the unshipped supply and the dummy supplier covers the
+    // unmet demand, both are forbidden for the mandatory parts.
+    pub fn with_ranges(a: Vec<(u64, u64)>, b: Vec<(u64, u64)>, c: Vec<Vec<u64>>) -> Result<Transportation, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+        if a.iter().chain(b.iter()).any(|&(min, max)| min > max) {
+            return Err(Range);
+        }
+
+        let min_s: u64 = a.iter().map(|&(min, _)| min).sum();
+        let max_s: u64 = a.iter().map(|&(_, max)| max).sum();
+        let min_d: u64 = b.iter().map(|&(min, _)| min).sum();
+        let max_d: u64 = b.iter().map(|&(_, max)| max).sum();
+        if min_s > max_d || min_d > max_s {
+            return Err(Infeasible);
+        }
+        if max_s + max_d == 0 {
+            return Err(NumOfSupOrCust);
+        }
+
+        // (amount, node, mandatory)
+        fn split(nodes: &[(u64, u64)], dummy: u64) -> Vec<(u64, Option<usize>, bool)> {
+            let mut parts = Vec::new();
+            for (i, &(min, max)) in nodes.iter().enumerate() {
+                if min > 0 {
+                    parts.push((min, Some(i), true));
+                }
+                if max > min {
+                    parts.push((max - min, Some(i), false));
+                }
+            }
+            if dummy > 0 {
+                parts.push((dummy, None, false));
+            }
+            parts
+        }
+
+        let rows = split(&a, max_d);
+        let cols = split(&b, max_s);
+        let m = checked_prohibitive_cost(&c, max_s)?;
+        let cost = rows.iter()
+                       .map(|&(_, row, mandatory_row)| {
+                           cols.iter()
+                               .map(|&(_, col, mandatory_col)| {
+                                   match (row, col) {
+                                       (Some(i), Some(j)) => c[i][j],
+                                       (Some(_), None) if mandatory_row => m,
+                                       (None, Some(_)) if mandatory_col => m,
+                                       _ => 0,
+                                   }
+                               })
+                               .collect()
+                       })
+                       .collect();
+
+        let mut t = Transportation::balanced(rows.iter().map(|r| r.0).collect(), cols.iter().map(|c| c.0).collect(), cost);
+        t.ranges = Some(Ranges {
+            supply: a,
+            demand: b,
+            cost: c,
+            rows: rows.iter().map(|r| r.1).collect(),
+            cols: cols.iter().map(|c| c.1).collect(),
+        });
+        Ok(t)
+    }
+}
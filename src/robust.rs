@@ -0,0 +1,142 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{plan_table, Transportation};
+use super::Error::{self, *};
+
+// The limit of the scenarios added by the minimax regret method
+const MAX_SCENARIOS: usize = 20;
+
+// Transportation with the costs known only as intervals [low, high].
+//
+// The regret of a plan in a scenario of the costs is how much more it costs than the plan
+// optimal in that scenario. The plan is compared with the scenarios met during the search:
+// the lowest and the highest costs, the midpoints and, for every plan tried, the scenario
+// with the highest costs on its routes and the lowest ones elsewhere. For plans that send
+// whole lots that scenario gives the largest regret, otherwise the regret is a lower bound.
+pub struct Robust {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    low: Vec<Vec<u64>>,
+    high: Vec<Vec<u64>>,
+    // The scenarios with their optimal costs
+    scenarios: Vec<(Vec<Vec<u64>>, u64)>,
+    plan: Vec<Vec<u64>>,
+}
+
+impl Robust {
+    // The optimal plan and its cost in the scenario
+    fn optimal(&self, c: &[Vec<u64>]) -> (Vec<Vec<u64>>, u64) {
+        let mut t = Transportation::new(self.supply.clone(), self.demand.clone(), c.to_vec()).unwrap();
+        t.potential_method();
+        (t.original_plan(), t.total_cost())
+    }
+
+    fn cost_of(plan: &[Vec<u64>], c: &[Vec<u64>]) -> u64 {
+        plan.iter().zip(c.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum()
+    }
+    // The highest costs on the routes of the plan and the lowest ones elsewhere
+    fn scenario_of(&self, plan: &[Vec<u64>]) -> Vec<Vec<u64>> {
+        (0..self.supply.len()).map(|i| (0..self.demand.len()).map(|j| if plan[i][j] > 0 { self.high[i][j] } else { self.low[i][j] }).collect()).collect()
+    }
+    // Add the scenario if it is new, return its optimal plan
+    fn add_scenario(&mut self, c: Vec<Vec<u64>>) -> Option<Vec<Vec<u64>>> {
+        if self.scenarios.iter().any(|s| s.0 == c) {
+            return None;
+        }
+        let (plan, z) = self.optimal(&c);
+        self.scenarios.push((c, z));
+        Some(plan)
+    }
+    // The largest regret of the plan over the known scenarios
+    fn regret_of(&self, plan: &[Vec<u64>]) -> u64 {
+        let c = self.scenario_of(plan);
+        let own = Robust::cost_of(plan, &c) - self.optimal(&c).1;
+        self.scenarios.iter().map(|s| Robust::cost_of(plan, &s.0) - s.1).fold(own, std::cmp::max)
+    }
+    // Start with the lowest costs, the highest ones and the midpoints
+    fn initial(&mut self) -> Vec<Vec<Vec<u64>>> {
+        self.scenarios = Vec::new();
+        let mid = self.low.iter().zip(self.high.iter()).map(|(l, h)| l.iter().zip(h.iter()).map(|(l, h)| (l + h) / 2).collect()).collect();
+        let mut plans = Vec::new();
+        for c in [self.low.clone(), self.high.clone(), mid] {
+            plans.extend(self.add_scenario(c));
+        }
+        plans
+    }
+    // The plan with the least cost when all costs are the highest
+    pub fn worst_case(&mut self) {
+        self.initial();
+        self.plan = self.optimal(&self.high).0;
+    }
+    // Take the plan with the least regret over the known scenarios and add the scenario
+    // where it regrets the most, until no new scenario is found
+    pub fn minimax_regret(&mut self) {
+        let mut plans = self.initial();
+        loop {
+            let regrets: Vec<u64> = plans.iter().map(|p| self.regret_of(p)).collect();
+            let best = (0..plans.len()).min_by_key(|&k| regrets[k]).unwrap();
+            self.plan = plans[best].clone();
+            if self.scenarios.len() >= MAX_SCENARIOS {
+                break;
+            }
+            let c = self.scenario_of(&self.plan);
+            match self.add_scenario(c) {
+                Some(plan) => plans.push(plan),
+                None => break,
+            }
+        }
+    }
+
+    pub fn plan(&self) -> &[Vec<u64>] {
+        &self.plan
+    }
+    // The cost of the plan when all costs are the lowest
+    pub fn best_cost(&self) -> u64 {
+        Robust::cost_of(&self.plan, &self.low)
+    }
+    // The cost of the plan when all costs are the highest
+    pub fn worst_cost(&self) -> u64 {
+        Robust::cost_of(&self.plan, &self.high)
+    }
+    // The largest regret of the plan over the known scenarios. It is only a lower bound
+    // of the regret over all costs in the intervals: the worst scenario of a plan is
+    // the maximum of a convex function, and finding it is NP-hard in general.
+    pub fn regret_bound(&self) -> u64 {
+        self.regret_of(&self.plan)
+    }
+
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan, &supply, &demand);
+        table.add_row(Row::new(vec![Cell::new("Наилучшая стоимость"), Cell::new(&format!("{}", self.best_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Наихудшая стоимость"), Cell::new(&format!("{}", self.worst_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Сожаление (нижняя оценка)"), Cell::new(&format!("{}", self.regret_bound()))]));
+        table.printstd();
+    }
+    // a is the supply, b is the demand, low and high are the bounds of the costs
+    pub fn new(a: Vec<u64>, b: Vec<u64>, low: Vec<Vec<u64>>, high: Vec<Vec<u64>>) -> Result<Robust, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != low.len() || a.len() != high.len() {
+            return Err(NumOfRows);
+        }
+        if low.iter().chain(high.iter()).any(|i| i.len() != b.len()) {
+            return Err(NumOfCols);
+        }
+        if low.iter().flat_map(|i| i.iter()).zip(high.iter().flat_map(|i| i.iter())).any(|(l, h)| l > h) {
+            return Err(Range);
+        }
+
+        Ok(Robust {
+            supply: a,
+            demand: b,
+            low,
+            high,
+            scenarios: Vec::new(),
+            plan: Vec::new(),
+        })
+    }
+}
@@ -0,0 +1,191 @@
+use super::{CostSource, Transportation};
+use super::Error::{self, *};
+
+// The values below are treated as zero
+const EPS: f64 = 1e-9;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Relation {
+    LessOrEqual,
+    GreaterOrEqual,
+    Equal,
+}
+
+// The coefficients, the relation and the right-hand side
+pub type Constraint = (Vec<f64>, Relation, f64);
+
+// The simplex tableau: every row is a constraint with the right-hand side in the last column,
+// basis[k] is the basic variable of the row k
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    fn rhs(&self, k: usize) -> f64 {
+        self.rows[k][self.rows[k].len() - 1]
+    }
+
+    fn pivot(&mut self, k: usize, j: usize) {
+        let p = self.rows[k][j];
+        for x in self.rows[k].iter_mut() {
+            *x /= p;
+        }
+        let row = self.rows[k].clone();
+        for (l, other) in self.rows.iter_mut().enumerate() {
+            let f = other[j];
+            if l != k && f != 0.0 {
+                for (x, y) in other.iter_mut().zip(row.iter()) {
+                    *x -= f * y;
+                }
+            }
+        }
+        self.basis[k] = j;
+    }
+    // Minimize the cost over the current basis, only the first `allowed` columns may enter it.
+    // Bland's rule keeps the method from cycling.
+    fn run(&mut self, cost: &[f64], allowed: usize) -> Result<(), Error> {
+        loop {
+            let entering = (0..allowed).find(|&j| {
+                let d = cost[j] - self.rows.iter().zip(self.basis.iter()).map(|(row, &b)| cost[b] * row[j]).sum::<f64>();
+                d < -EPS
+            });
+            let j = match entering {
+                Some(j) => j,
+                None => return Ok(()),
+            };
+
+            let mut leaving: Option<(usize, f64)> = None;
+            for k in 0..self.rows.len() {
+                if self.rows[k][j] > EPS {
+                    let ratio = self.rhs(k) / self.rows[k][j];
+                    leaving = match leaving {
+                        Some((l, r)) if r < ratio - EPS || (r <= ratio + EPS && self.basis[l] < self.basis[k]) => Some((l, r)),
+                        _ => Some((k, ratio)),
+                    };
+                }
+            }
+            match leaving {
+                Some((k, _)) => self.pivot(k, j),
+                None => return Err(Unbounded),
+            }
+        }
+    }
+}
+
+// Two-phase simplex method. Minimize c·x subject to the constraints (a, relation, b) and x >= 0.
+// Return the optimal x and the value of the objective.
+pub fn minimize(c: &[f64], constraints: &[Constraint]) -> Result<(Vec<f64>, f64), Error> {
+    let n = c.len();
+    let slacks = constraints.iter().filter(|r| r.1 != Relation::Equal).count();
+    let artificials = constraints.iter().filter(|r| r.1 != Relation::LessOrEqual).count();
+    let total = n + slacks + artificials;
+
+    // The columns are the variables, then the slacks, then the artificial variables
+    let mut tableau = Tableau {
+        rows: Vec::new(),
+        basis: Vec::new(),
+    };
+    let mut slack = n;
+    let mut artificial = n + slacks;
+    for &(ref a, relation, b) in constraints {
+        assert_eq!(a.len(), n);
+        let (sign, relation) = match relation {
+            Relation::LessOrEqual if b < 0.0 => (-1.0, Relation::GreaterOrEqual),
+            Relation::GreaterOrEqual if b < 0.0 => (-1.0, Relation::LessOrEqual),
+            _ if b < 0.0 => (-1.0, relation),
+            _ => (1.0, relation),
+        };
+        let mut row: Vec<f64> = a.iter().map(|x| x * sign).collect();
+        row.resize(total + 1, 0.0);
+        row[total] = b * sign;
+        match relation {
+            Relation::LessOrEqual => {
+                row[slack] = 1.0;
+                tableau.basis.push(slack);
+                slack += 1;
+            }
+            Relation::GreaterOrEqual => {
+                row[slack] = -1.0;
+                row[artificial] = 1.0;
+                tableau.basis.push(artificial);
+                slack += 1;
+                artificial += 1;
+            }
+            Relation::Equal => {
+                row[artificial] = 1.0;
+                tableau.basis.push(artificial);
+                artificial += 1;
+            }
+        }
+        tableau.rows.push(row);
+    }
+
+    // Phase 1: find a feasible basis by minimizing the sum of the artificial variables
+    let phase1: Vec<f64> = (0..total).map(|j| if j >= n + slacks { 1.0 } else { 0.0 }).collect();
+    tableau.run(&phase1, total)?;
+    let infeasibility: f64 = tableau.basis.iter().enumerate().filter(|&(_, &b)| b >= n + slacks).map(|(k, _)| tableau.rhs(k)).sum();
+    if infeasibility > EPS * (1.0 + constraints.iter().map(|r| r.2.abs()).sum::<f64>()) {
+        return Err(Infeasible);
+    }
+    // Drive the artificial variables out of the basis, the rows where it is impossible are redundant
+    let mut k = 0;
+    while k < tableau.rows.len() {
+        if tableau.basis[k] >= n + slacks {
+            match (0..n + slacks).find(|&j| tableau.rows[k][j].abs() > EPS) {
+                Some(j) => tableau.pivot(k, j),
+                None => {
+                    tableau.rows.remove(k);
+                    tableau.basis.remove(k);
+                    continue;
+                }
+            }
+        }
+        k += 1;
+    }
+
+    // Phase 2: the original objective without the artificial variables
+    let mut phase2 = c.to_vec();
+    phase2.resize(total, 0.0);
+    tableau.run(&phase2, n + slacks)?;
+
+    let mut x = vec![0.0; n];
+    for (k, &b) in tableau.basis.iter().enumerate() {
+        if b < n {
+            x[b] = tableau.rhs(k);
+        }
+    }
+    let z = x.iter().zip(c.iter()).map(|(x, c)| x * c).sum();
+    Ok((x, z))
+}
+
+impl<C: CostSource> Transportation<C> {
+    // The balanced problem as a linear program: the variable of the cell (i, j) is i * cols + j,
+    // every row and every column is an equality
+    pub fn linear_program(&self) -> (Vec<f64>, Vec<Constraint>) {
+        let (rows, cols) = (self.supply.len(), self.demand.len());
+        let c: Vec<f64> = (0..rows * cols).map(|n| self.cost_of(n / cols, n % cols) as f64).collect();
+        let mut constraints = Vec::new();
+        for (i, &a) in self.supply.iter().enumerate() {
+            let row = (0..rows * cols).map(|n| if n / cols == i { 1.0 } else { 0.0 }).collect();
+            constraints.push((row, Relation::Equal, a as f64));
+        }
+        for (j, &b) in self.demand.iter().enumerate() {
+            let col = (0..rows * cols).map(|n| if n % cols == j { 1.0 } else { 0.0 }).collect();
+            constraints.push((col, Relation::Equal, b as f64));
+        }
+        (c, constraints)
+    }
+    // The optimal cost found by the simplex method
+    pub fn lp_cost(&self) -> Result<f64, Error> {
+        let (c, constraints) = self.linear_program();
+        minimize(&c, &constraints).map(|(_, z)| z)
+    }
+    // Compare the cost of the current plan with the simplex method.
+    // Return the cost of the linear program and whether they are equal.
+    pub fn check_with_lp(&self) -> Result<(f64, bool), Error> {
+        let z = self.lp_cost()?;
+        let cost = self.total_cost() as f64;
+        Ok((z, (z - cost).abs() <= 1e-6 * (1.0 + cost)))
+    }
+}
@@ -0,0 +1,179 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+use prettytable::Table;
+
+use super::{plan_table, Transportation};
+use super::Error::{self, *};
+
+// The number of iterations of the subgradient method
+const ITERATIONS: usize = 50;
+
+// Solid transportation problem: every unit is sent from a supplier to a customer
+// by one of the conveyances, each conveyance has its own capacity and costs
+pub struct Solid {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    capacity: Vec<u64>,
+    cost: Vec<Vec<Vec<u64>>>,
+    plan: Vec<Vec<Vec<u64>>>,
+    bound: i64,
+}
+
+impl Solid {
+    // Solve the transportation problem and drop the dummies from the plan
+    fn solve_plain(a: &[u64], b: &[u64], c: Vec<Vec<u64>>) -> (Vec<Vec<u64>>, u64) {
+        let mut t = Transportation::new(a.to_vec(), b.to_vec(), c).unwrap();
+        t.potential_method();
+        (t.original_plan(), t.total_cost())
+    }
+    // Distribute the shipments between the conveyances at the least cost.
+    // It is a transportation problem from the used routes to the conveyances.
+    fn assign(&self, x: &[Vec<u64>]) -> Vec<Vec<Vec<u64>>> {
+        let mut plan = vec![vec![vec![0; self.capacity.len()]; self.demand.len()]; self.supply.len()];
+        let mut routes = Vec::new();
+        for (i, row) in x.iter().enumerate() {
+            for (j, &amount) in row.iter().enumerate() {
+                if amount > 0 {
+                    routes.push((i, j, amount));
+                }
+            }
+        }
+        if routes.is_empty() {
+            return plan;
+        }
+
+        let a: Vec<u64> = routes.iter().map(|r| r.2).collect();
+        let c = routes.iter().map(|&(i, j, _)| self.cost[i][j].clone()).collect();
+        let (y, _) = Solid::solve_plain(&a, &self.capacity, c);
+        for (&(i, j, _), y) in routes.iter().zip(y.iter()) {
+            plan[i][j] = y.clone();
+        }
+        plan
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        let mut z = 0;
+        for (x, c) in self.plan.iter().zip(self.cost.iter()) {
+            for (x, c) in x.iter().zip(c.iter()) {
+                z += x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>();
+            }
+        }
+        z
+    }
+    // Lagrangian relaxation of the capacities of the conveyances. Each conveyance gets a price,
+    // then every route takes the cheapest conveyance and the problem becomes a usual
+    // transportation problem. The prices follow the overload of the conveyances and give
+    // a lower bound of the cost. The shipments of every step are distributed between
+    // the conveyances within their capacities to get a feasible plan.
+    pub fn solve(&mut self) -> Result<(), Error> {
+        let shipped = std::cmp::min(self.supply.iter().sum::<u64>(), self.demand.iter().sum());
+        if self.capacity.iter().sum::<u64>() < shipped {
+            return Err(Infeasible);
+        }
+
+        let max = self.cost.iter().flat_map(|c| c.iter()).flat_map(|c| c.iter()).cloned().max().unwrap_or(0);
+        let mut price = vec![0; self.capacity.len()];
+        let mut best: Option<(Vec<Vec<Vec<u64>>>, u64)> = None;
+        self.bound = 0;
+
+        for n in 0..ITERATIONS {
+            let choice: Vec<Vec<usize>> = self.cost
+                                              .iter()
+                                              .map(|row| {
+                                                  row.iter()
+                                                     .map(|c| (0..c.len()).min_by_key(|&k| c[k] + price[k]).unwrap())
+                                                     .collect()
+                                              })
+                                              .collect();
+            let c = choice.iter()
+                          .enumerate()
+                          .map(|(i, row)| row.iter().enumerate().map(|(j, &k)| self.cost[i][j][k] + price[k]).collect())
+                          .collect();
+            let (x, z) = Solid::solve_plain(&self.supply, &self.demand, c);
+
+            let mut load = vec![0; self.capacity.len()];
+            for (x, choice) in x.iter().zip(choice.iter()) {
+                for (x, &k) in x.iter().zip(choice.iter()) {
+                    load[k] += x;
+                }
+            }
+            let value = z as i64 - price.iter().zip(self.capacity.iter()).map(|(p, e)| (p * e) as i64).sum::<i64>();
+            self.bound = std::cmp::max(self.bound, value);
+
+            self.plan = self.assign(&x);
+            let z = self.total_cost();
+            if best.as_ref().is_none_or(|b| z < b.1) {
+                best = Some((self.plan.clone(), z));
+            }
+            if z as i64 <= self.bound {
+                break;
+            }
+
+            let step = std::cmp::max(1, max / (n as u64 + 1)) as i64;
+            for (p, (l, e)) in price.iter_mut().zip(load.iter().zip(self.capacity.iter())) {
+                *p = std::cmp::max(0, *p as i64 + step * (*l as i64 - *e as i64).signum()) as u64;
+            }
+        }
+
+        self.plan = best.unwrap().0;
+        Ok(())
+    }
+    // The amount sent from the supplier i to the customer j by the conveyance k is plan()[i][j][k]
+    pub fn plan(&self) -> &[Vec<Vec<u64>>] {
+        &self.plan
+    }
+    // The lower bound of the cost given by the Lagrangian relaxation
+    pub fn lower_bound(&self) -> u64 {
+        self.bound as u64
+    }
+    // One table per conveyance
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        for (k, e) in self.capacity.iter().enumerate() {
+            println!("Транспорт {} (вместимость {})", k + 1, e);
+            let plan: Vec<Vec<u64>> = self.plan.iter().map(|row| row.iter().map(|x| x[k]).collect()).collect();
+            let cost: u64 = (0..self.supply.len())
+                                .flat_map(|i| (0..self.demand.len()).map(move |j| (i, j)))
+                                .map(|(i, j)| plan[i][j] * self.cost[i][j][k])
+                                .sum();
+            let mut table = plan_table(&plan, &supply, &demand);
+            table.add_row(Row::new(vec![Cell::new("Стоимость"), Cell::new(&format!("{}", cost))]));
+            table.printstd();
+        }
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.add_row(Row::new(vec![Cell::new("Нижняя оценка"), Cell::new(&format!("{}", self.lower_bound()))]));
+        table.printstd();
+    }
+    // c[i][j][k] is the cost of sending a unit from the supplier i to the customer j by the conveyance k,
+    // e[k] is the capacity of the conveyance k
+    pub fn new(a: Vec<u64>, b: Vec<u64>, e: Vec<u64>, c: Vec<Vec<Vec<u64>>>) -> Result<Solid, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if e.is_empty() {
+            return Err(NumOfConveyances);
+        }
+        if a.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+            if i.iter().any(|k| k.len() != e.len()) {
+                return Err(NumOfConveyances);
+            }
+        }
+
+        Ok(Solid {
+            supply: a,
+            demand: b,
+            capacity: e,
+            cost: c,
+            plan: Vec::new(),
+            bound: 0,
+        })
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use super::{Basis, Error, Transportation};
+
+// A method of solving the balanced transportation problem.
+// The plan has the rows and the columns of the problem, the dummies included.
+pub trait Solver {
+    fn solve(&self, t: &Transportation) -> Vec<Vec<u64>>;
+}
+
+// The costs of the problem as rows
+fn costs(t: &Transportation) -> Vec<Vec<u64>> {
+    (0..t.cost.rows()).map(|i| t.cost[i].to_vec()).collect()
+}
+
+// The potential method of Transportation
+pub struct PotentialMethod;
+
+impl Solver for PotentialMethod {
+    fn solve(&self, t: &Transportation) -> Vec<Vec<u64>> {
+        let mut t = Transportation::balanced(t.supply.clone(), t.demand.clone(), costs(t));
+        t.potential_method();
+        t.plan()
+    }
+}
+
+// The residual network of the problem: the suppliers are the nodes 0..rows, the customers follow them.
+// Every route is an arc with the reverse one next to it.
+struct Network {
+    arcs: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    residual: Vec<u64>,
+    cost: Vec<i128>,
+    rows: usize,
+    cols: usize,
+}
+
+impl Network {
+    // The routes have the capacity of the whole supply, the costs are multiplied by the scale
+    fn new(t: &Transportation, scale: i128) -> Network {
+        let (rows, cols) = (t.supply.len(), t.demand.len());
+        let total: u64 = t.supply.iter().sum();
+        let mut n = Network {
+            arcs: vec![Vec::new(); rows + cols],
+            to: Vec::new(),
+            residual: Vec::new(),
+            cost: Vec::new(),
+            rows,
+            cols,
+        };
+        for i in 0..rows {
+            for j in 0..cols {
+                let c = t.cost[i][j] as i128 * scale;
+                for &(from, to, cap, cost) in &[(i, rows + j, total, c), (rows + j, i, 0, -c)] {
+                    n.arcs[from].push(n.to.len());
+                    n.to.push(to);
+                    n.residual.push(cap);
+                    n.cost.push(cost);
+                }
+            }
+        }
+        n
+    }
+    // The amount sent by every route
+    fn plan(&self) -> Vec<Vec<u64>> {
+        (0..self.rows).map(|i| (0..self.cols).map(|j| self.residual[2 * (i * self.cols + j) + 1]).collect()).collect()
+    }
+}
+
+// Successive shortest paths: the goods are sent from the suppliers that still have them
+// to the customers that still need them by the cheapest path in the residual network.
+// The potentials keep the reduced costs non-negative, so the paths are found by Dijkstra.
+pub struct SuccessiveShortestPath;
+
+impl Solver for SuccessiveShortestPath {
+    fn solve(&self, t: &Transportation) -> Vec<Vec<u64>> {
+        let mut n = Network::new(t, 1);
+        let nodes = n.rows + n.cols;
+        let mut excess: Vec<i128> = t.supply.iter().map(|&a| a as i128).chain(t.demand.iter().map(|&b| -(b as i128))).collect();
+        let mut potential = vec![0i128; nodes];
+
+        loop {
+            // Dijkstra from all suppliers with the goods left
+            let mut dist: Vec<Option<i128>> = (0..nodes).map(|v| if excess[v] > 0 { Some(0) } else { None }).collect();
+            let mut via: Vec<Option<usize>> = vec![None; nodes];
+            let mut done = vec![false; nodes];
+            loop {
+                let next = (0..nodes).filter(|&v| !done[v] && dist[v].is_some()).min_by_key(|&v| dist[v]);
+                let v = match next {
+                    Some(v) => v,
+                    None => break,
+                };
+                done[v] = true;
+                for &e in &n.arcs[v] {
+                    let w = n.to[e];
+                    if n.residual[e] > 0 && !done[w] {
+                        let d = dist[v].unwrap() + n.cost[e] + potential[v] - potential[w];
+                        if dist[w].is_none_or(|x| d < x) {
+                            dist[w] = Some(d);
+                            via[w] = Some(e);
+                        }
+                    }
+                }
+            }
+
+            // The nearest customer that still needs the goods
+            let sink = (n.rows..nodes).filter(|&v| excess[v] < 0 && dist[v].is_some()).min_by_key(|&v| dist[v]);
+            let sink = match sink {
+                Some(v) => v,
+                None => break,
+            };
+            // The nodes farther than the customer get its distance, so the reduced costs stay non-negative
+            let limit = dist[sink].unwrap();
+            for v in 0..nodes {
+                potential[v] += dist[v].map_or(limit, |d| std::cmp::min(d, limit));
+            }
+
+            let mut path = Vec::new();
+            let mut v = sink;
+            while let Some(e) = via[v] {
+                path.push(e);
+                v = n.to[e ^ 1];
+            }
+            let amount = path.iter().map(|&e| n.residual[e]).fold(std::cmp::min(excess[v], -excess[sink]) as u64, std::cmp::min);
+            for &e in &path {
+                n.residual[e] -= amount;
+                n.residual[e ^ 1] += amount;
+            }
+            excess[v] -= amount as i128;
+            excess[sink] += amount as i128;
+        }
+        n.plan()
+    }
+}
+
+// Cost scaling (Goldberg and Tarjan): the plan is ε-optimal if no arc of the residual network
+// has the reduced cost below -ε. Every stage halves ε, saturates the arcs with negative reduced
+// costs and pushes the excess along the arcs with negative reduced costs, lowering the prices
+// of the nodes that have none. With the costs multiplied by the number of nodes,
+// the plan is optimal when ε reaches 1.
+pub struct CostScaling;
+
+impl Solver for CostScaling {
+    fn solve(&self, t: &Transportation) -> Vec<Vec<u64>> {
+        let nodes = (t.supply.len() + t.demand.len()) as i128;
+        let mut n = Network::new(t, nodes);
+        // Every stage ends with no excess left
+        let mut excess: Vec<i128> = t.supply.iter().map(|&a| a as i128).chain(t.demand.iter().map(|&b| -(b as i128))).collect();
+        let mut price = vec![0i128; excess.len()];
+        let mut epsilon = n.cost.iter().cloned().max().unwrap_or(0);
+        let reduced = |n: &Network, price: &[i128], e: usize| n.cost[e] + price[n.to[e ^ 1]] - price[n.to[e]];
+
+        loop {
+            epsilon = std::cmp::max(epsilon / 2, 1);
+            for e in 0..n.to.len() {
+                if n.residual[e] > 0 && reduced(&n, &price, e) < 0 {
+                    let x = n.residual[e];
+                    n.residual[e] = 0;
+                    n.residual[e ^ 1] += x;
+                    excess[n.to[e ^ 1]] -= x as i128;
+                    excess[n.to[e]] += x as i128;
+                }
+            }
+
+            let mut active: VecDeque<usize> = (0..excess.len()).filter(|&v| excess[v] > 0).collect();
+            while let Some(v) = active.pop_front() {
+                while excess[v] > 0 {
+                    let admissible = n.arcs[v].iter().cloned().find(|&e| n.residual[e] > 0 && reduced(&n, &price, e) < 0);
+                    match admissible {
+                        Some(e) => {
+                            let w = n.to[e];
+                            let x = std::cmp::min(excess[v] as u64, n.residual[e]);
+                            n.residual[e] -= x;
+                            n.residual[e ^ 1] += x;
+                            excess[v] -= x as i128;
+                            if excess[w] <= 0 && excess[w] + x as i128 > 0 {
+                                active.push_back(w);
+                            }
+                            excess[w] += x as i128;
+                        }
+                        None => {
+                            price[v] = n.arcs[v]
+                                        .iter()
+                                        .filter(|&&e| n.residual[e] > 0)
+                                        .map(|&e| price[n.to[e]] - n.cost[e] - epsilon)
+                                        .max()
+                                        .unwrap();
+                        }
+                    }
+                }
+            }
+            if epsilon == 1 {
+                break;
+            }
+        }
+        n.plan()
+    }
+}
+
+// The cells on the path between two nodes of the forest
+fn forest_path(adjacent: &[Vec<(usize, (usize, usize))>], from: usize, to: usize) -> Option<Vec<(usize, usize)>> {
+    let mut via: Vec<Option<(usize, (usize, usize))>> = vec![None; adjacent.len()];
+    let mut seen = vec![false; adjacent.len()];
+    let mut queue = VecDeque::new();
+    seen[from] = true;
+    queue.push_back(from);
+    while let Some(node) = queue.pop_front() {
+        for &(next, cell) in &adjacent[node] {
+            if !seen[next] {
+                seen[next] = true;
+                via[next] = Some((node, cell));
+                queue.push_back(next);
+            }
+        }
+    }
+    if !seen[to] {
+        return None;
+    }
+    let mut path = Vec::new();
+    let mut node = to;
+    while let Some((prev, cell)) = via[node] {
+        path.push(cell);
+        node = prev;
+    }
+    path.reverse();
+    Some(path)
+}
+
+// A cycle of the cells with positive amounts, the cells share a column and a row in turn
+fn support_cycle(plan: &[Vec<u64>]) -> Option<Vec<(usize, usize)>> {
+    let (rows, cols) = (plan.len(), plan[0].len());
+    // The forest of the cells seen so far, the column j is the node rows + j
+    let mut adjacent = vec![Vec::new(); rows + cols];
+    for i in 0..rows {
+        for j in 0..cols {
+            if plan[i][j] > 0 {
+                if let Some(path) = forest_path(&adjacent, rows + j, i) {
+                    let mut cycle = vec![(i, j)];
+                    cycle.extend(path);
+                    return Some(cycle);
+                }
+                adjacent[i].push((rows + j, (i, j)));
+                adjacent[rows + j].push((i, (i, j)));
+            }
+        }
+    }
+    None
+}
+
+impl Transportation {
+    // Replace the plan with the one found by the solver. The cycles of the plan are cancelled in the
+    // direction that doesn't raise the cost and the basis is completed. The pivots that follow only
+    // change the basis until its potentials are optimal too, so Error::NotOptimal is returned
+    // if one of them lowers the cost.
+    pub fn solve_with(&mut self, solver: &dyn Solver) -> Result<(), Error> {
+        let mut plan = solver.solve(self);
+        while let Some(cycle) = support_cycle(&plan) {
+            let cost: i64 = cycle.iter()
+                                 .enumerate()
+                                 .map(|(k, &(i, j))| if k % 2 == 0 { self.cost_of(i, j) as i64 } else { -(self.cost_of(i, j) as i64) })
+                                 .sum();
+            // The cells that decrease
+            let first = if cost > 0 { 0 } else { 1 };
+            let theta = cycle.iter().skip(first).step_by(2).map(|&(i, j)| plan[i][j]).min().unwrap();
+            for (k, &(i, j)) in cycle.iter().enumerate() {
+                if k % 2 == first {
+                    plan[i][j] -= theta;
+                } else {
+                    plan[i][j] += theta;
+                }
+            }
+        }
+        self.trans = Basis::new(self.supply.len(), self.demand.len());
+        for (i, row) in plan.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                if x > 0 {
+                    self.trans.set(i, j, Some(x));
+                }
+            }
+        }
+        self.replenish();
+        let cost = self.total_cost();
+        while let Some((i, j, _)) = self.check() {
+            self.pivot(i, j);
+            if self.total_cost() < cost {
+                return Err(Error::NotOptimal);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+use prettytable::Table;
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::prohibitive_cost_for;
+use super::Error::{self, *};
+
+// An allowed route: the supplier, the customer and the cost of a unit
+pub type Route = (usize, usize, u64);
+
+// Transportation where only the allowed routes are stored.
+//
+// The suppliers and the customers are the nodes of a bipartite graph, the customer j is the node
+// rows + j. The basis is a spanning tree of the graph, so the potentials are found by a walk over
+// the tree and the cycle of an entering route is the path in the tree between its ends.
+// The routes missing from the list are never used: the basis is completed by artificial
+// routes with the prohibitive cost, the problem is infeasible if they keep any flow.
+pub struct SparseTransportation {
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    // The number of suppliers and customers without the dummies
+    shape: (usize, usize),
+    // The routes, the dummy and the artificial ones included
+    routes: Vec<Route>,
+    flow: Vec<u64>,
+    basic: Vec<bool>,
+    // The basic routes at every node
+    tree: Vec<Vec<usize>>,
+    // The routes from this one on are artificial
+    artificial: usize,
+}
+
+// The root of the node in the disjoint sets
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+impl SparseTransportation {
+    fn nodes(&self) -> usize {
+        self.supply.len() + self.demand.len()
+    }
+
+    fn add_basic(&mut self, route: Route, flow: u64) {
+        let k = self.routes.len();
+        self.routes.push(route);
+        self.flow.push(flow);
+        self.basic.push(true);
+        self.tree[route.0].push(k);
+        self.tree[self.supply.len() + route.1].push(k);
+    }
+
+    fn enter(&mut self, k: usize) {
+        let (i, j, _) = self.routes[k];
+        self.basic[k] = true;
+        self.tree[i].push(k);
+        self.tree[self.supply.len() + j].push(k);
+    }
+
+    fn leave(&mut self, k: usize) {
+        let (i, j, _) = self.routes[k];
+        self.basic[k] = false;
+        let rows = self.supply.len();
+        for node in [i, rows + j] {
+            self.tree[node].retain(|&r| r != k);
+        }
+    }
+    // The other end of the route
+    fn across(&self, k: usize, node: usize) -> usize {
+        let (i, j, _) = self.routes[k];
+        if node == i {
+            self.supply.len() + j
+        } else {
+            i
+        }
+    }
+    // The least cost method over the allowed routes, the rest is sent by the artificial ones.
+    // Then the basis is completed to a spanning tree. Return Error::Overflow if the cost
+    // of the artificial routes doesn't fit.
+    fn initial(&mut self) -> Result<(), Error> {
+        self.routes.truncate(self.artificial);
+        self.flow = vec![0; self.artificial];
+        self.basic = vec![false; self.artificial];
+        for node in self.tree.iter_mut() {
+            node.clear();
+        }
+        let rows = self.supply.len();
+        let mut a = self.supply.clone();
+        let mut b = self.demand.clone();
+        let mut order: Vec<usize> = (0..self.artificial).collect();
+        order.sort_by_key(|&k| self.routes[k].2);
+
+        let mut parent: Vec<usize> = (0..self.nodes()).collect();
+        for &k in &order {
+            let (i, j, _) = self.routes[k];
+            let x = std::cmp::min(a[i], b[j]);
+            if x > 0 {
+                a[i] -= x;
+                b[j] -= x;
+                self.flow[k] = x;
+                self.enter(k);
+                let (ri, rj) = (find(&mut parent, i), find(&mut parent, rows + j));
+                parent[ri] = rj;
+            }
+        }
+
+        let max = self.routes.iter().map(|r| r.2).max().unwrap_or(0);
+        let m = prohibitive_cost_for(max, self.supply.iter().sum(), self.nodes())?;
+        let (mut i, mut j) = (0, 0);
+        while i < rows && j < b.len() {
+            let x = std::cmp::min(a[i], b[j]);
+            if x > 0 {
+                a[i] -= x;
+                b[j] -= x;
+                self.add_basic((i, j, m), x);
+                let (ri, rj) = (find(&mut parent, i), find(&mut parent, rows + j));
+                parent[ri] = rj;
+            }
+            if a[i] == 0 {
+                i += 1;
+            }
+            if j < b.len() && b[j] == 0 {
+                j += 1;
+            }
+        }
+
+        for &k in &order {
+            if !self.basic[k] {
+                let (i, j, _) = self.routes[k];
+                let (ri, rj) = (find(&mut parent, i), find(&mut parent, rows + j));
+                if ri != rj {
+                    parent[ri] = rj;
+                    self.enter(k);
+                }
+            }
+        }
+        for j in 0..self.demand.len() {
+            let (r0, rj) = (find(&mut parent, 0), find(&mut parent, rows + j));
+            if r0 != rj {
+                parent[rj] = r0;
+                self.add_basic((0, j, m), 0);
+            }
+        }
+        for i in 1..rows {
+            let (r0, ri) = (find(&mut parent, 0), find(&mut parent, i));
+            if r0 != ri {
+                parent[ri] = r0;
+                self.add_basic((i, 0, m), 0);
+            }
+        }
+        Ok(())
+    }
+    // The potentials of the nodes found by a walk over the tree from the first supplier,
+    // together with the route that leads to every node from the root
+    fn potentials(&self) -> (Vec<i64>, Vec<Option<usize>>) {
+        let mut p = vec![0; self.nodes()];
+        let mut via = vec![None; self.nodes()];
+        let mut seen = vec![false; self.nodes()];
+        let mut stack = vec![0];
+        seen[0] = true;
+        while let Some(node) = stack.pop() {
+            for &k in &self.tree[node] {
+                let next = self.across(k, node);
+                if !seen[next] {
+                    seen[next] = true;
+                    p[next] = self.routes[k].2 as i64 - p[node];
+                    via[next] = Some(k);
+                    stack.push(next);
+                }
+            }
+        }
+        (p, via)
+    }
+    // The route with the most negative difference of the potentials
+    fn check(&self, p: &[i64]) -> Option<usize> {
+        let rows = self.supply.len();
+        let mut min = None;
+        for (k, &(i, j, c)) in self.routes.iter().enumerate() {
+            if !self.basic[k] {
+                let d = c as i64 - p[i] - p[rows + j];
+                if d < min.map_or(0, |m: (usize, i64)| m.1) {
+                    min = Some((k, d));
+                }
+            }
+        }
+        min.map(|m| m.0)
+    }
+    // The path in the tree from the node to the root
+    fn path(&self, mut node: usize, via: &[Option<usize>]) -> Vec<usize> {
+        let mut path = Vec::new();
+        while let Some(k) = via[node] {
+            path.push(k);
+            node = self.across(k, node);
+        }
+        path
+    }
+    // Return Error::Infeasible if the allowed routes can't carry the goods
+    pub fn potential_method(&mut self) -> Result<(), Error> {
+        self.initial()?;
+        let rows = self.supply.len();
+
+        loop {
+            let (p, via) = self.potentials();
+            let entering = match self.check(&p) {
+                Some(k) => k,
+                None => break,
+            };
+            let (i, j, _) = self.routes[entering];
+
+            // The cycle is the entering route, then the path from its customer to its supplier.
+            // Both paths go to the root, their common part is dropped.
+            let mut from_j = self.path(rows + j, &via);
+            let mut from_i = self.path(i, &via);
+            while !from_j.is_empty() && !from_i.is_empty() && from_j.last() == from_i.last() {
+                from_j.pop();
+                from_i.pop();
+            }
+            from_i.reverse();
+            let cycle: Vec<usize> = from_j.into_iter().chain(from_i).collect();
+
+            // The routes of the cycle decrease and increase in turn, starting from the customer
+            let mut leaving = cycle[0];
+            for &k in cycle.iter().step_by(2) {
+                let (f, l) = (self.flow[k], self.flow[leaving]);
+                if f < l || f == l && self.routes[k].2 > self.routes[leaving].2 {
+                    leaving = k;
+                }
+            }
+            let theta = self.flow[leaving];
+            for (n, &k) in cycle.iter().enumerate() {
+                if n % 2 == 0 {
+                    self.flow[k] -= theta;
+                } else {
+                    self.flow[k] += theta;
+                }
+            }
+            self.flow[entering] = theta;
+            self.leave(leaving);
+            self.enter(entering);
+        }
+
+        if self.flow[self.artificial..].iter().any(|&x| x > 0) {
+            return Err(Infeasible);
+        }
+        Ok(())
+    }
+    // The used routes without the dummies
+    pub fn plan(&self) -> Vec<Route> {
+        let mut plan: Vec<Route> = self.routes[..self.artificial]
+                                       .iter()
+                                       .zip(self.flow.iter())
+                                       .filter(|&(r, &x)| x > 0 && r.0 < self.shape.0 && r.1 < self.shape.1)
+                                       .map(|(r, &x)| (r.0, r.1, x))
+                                       .collect();
+        plan.sort();
+        plan
+    }
+
+    pub fn total_cost(&self) -> u64 {
+        self.routes.iter().zip(self.flow.iter()).map(|(r, x)| r.2 * x).sum()
+    }
+    // The table of the used routes
+    pub fn printstd(&self) {
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Поставщик"), Cell::new("Потребитель"), Cell::new("Количество")]));
+        for (i, j, x) in self.plan() {
+            table.add_row(Row::new(vec![Cell::new(&format!("A{}", i + 1)), Cell::new(&format!("B{}", j + 1)), Cell::new(&format!("{}", x))]));
+        }
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", self.total_cost()))]));
+        table.printstd();
+    }
+    // a is the supply, b is the demand and routes are the allowed routes with their costs
+    pub fn new(a: Vec<u64>, b: Vec<u64>, mut routes: Vec<Route>) -> Result<SparseTransportation, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if routes.iter().any(|r| r.0 >= a.len() || r.1 >= b.len()) {
+            return Err(RouteIndex);
+        }
+        let mut pairs: Vec<(usize, usize)> = routes.iter().map(|r| (r.0, r.1)).collect();
+        pairs.sort();
+        pairs.dedup();
+        if pairs.len() != routes.len() {
+            return Err(RouteIndex);
+        }
+
+        // The dummy is connected with everyone at no cost
+        let (rows, cols) = (a.len(), b.len());
+        let sum_s: u64 = a.iter().sum();
+        let sum_d: u64 = b.iter().sum();
+        let (mut supply, mut demand) = (a, b);
+        if sum_s > sum_d {
+            demand.push(sum_s - sum_d);
+            routes.extend((0..rows).map(|i| (i, cols, 0)));
+        }
+        if sum_s < sum_d {
+            supply.push(sum_d - sum_s);
+            routes.extend((0..cols).map(|j| (rows, j, 0)));
+        }
+
+        let n = routes.len();
+        Ok(SparseTransportation {
+            tree: vec![Vec::new(); supply.len() + demand.len()],
+            supply,
+            demand,
+            shape: (rows, cols),
+            routes,
+            flow: vec![0; n],
+            basic: vec![false; n],
+            artificial: n,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+use prettytable::Table;
+
+use super::{plan_table, Transportation};
+use super::Error::{self, *};
+
+// Two-stage planning with the demand known only as weighted scenarios. The goods are shipped
+// before the demand is known, then every customer pays a penalty for each unit of shortage
+// and for each unit of surplus.
+//
+// The expected penalty of a customer is convex and piecewise linear in the amount shipped to it
+// with the breaks at the demands of the scenarios. So every customer is split into columns,
+// one per piece, with the marginal penalty of the piece added to the costs. The convexity
+// makes the cheaper pieces fill first. The costs are multiplied by the sum of the weights
+// to stay in integers and shifted to be non-negative. The extra row leaves the pieces
+// unfilled and the extra column takes the supply that is not shipped.
+pub struct Stochastic {
+    supply: Vec<u64>,
+    cost: Vec<Vec<u64>>,
+    scenarios: Vec<(u64, Vec<u64>)>,
+    shortage: Vec<u64>,
+    surplus: Vec<u64>,
+    // The customer of every piece
+    pieces: Vec<usize>,
+    inner: Transportation,
+}
+
+impl Stochastic {
+    fn customers(&self) -> usize {
+        self.shortage.len()
+    }
+
+    pub fn solve(&mut self) {
+        self.inner.potential_method();
+    }
+    // The first-stage plan
+    pub fn plan(&self) -> Vec<Vec<u64>> {
+        let mut plan = vec![vec![0; self.customers()]; self.supply.len()];
+        for (i, row) in plan.iter_mut().enumerate() {
+            for (k, &j) in self.pieces.iter().enumerate() {
+                row[j] += self.inner.trans.get(i, k).unwrap_or(0);
+            }
+        }
+        plan
+    }
+    // The amount shipped to every customer
+    pub fn shipped(&self) -> Vec<u64> {
+        let plan = self.plan();
+        (0..self.customers()).map(|j| plan.iter().map(|row| row[j]).sum()).collect()
+    }
+    // The demand that is not met in the scenario
+    pub fn shortages(&self, scenario: usize) -> Vec<u64> {
+        self.shipped().iter().zip(self.scenarios[scenario].1.iter()).map(|(&x, &d)| d.saturating_sub(x)).collect()
+    }
+    // The amount shipped above the demand in the scenario
+    pub fn surpluses(&self, scenario: usize) -> Vec<u64> {
+        self.shipped().iter().zip(self.scenarios[scenario].1.iter()).map(|(&x, &d)| x.saturating_sub(d)).collect()
+    }
+
+    pub fn transport_cost(&self) -> u64 {
+        self.plan().iter().zip(self.cost.iter()).map(|(x, c)| x.iter().zip(c.iter()).map(|(x, c)| x * c).sum::<u64>()).sum()
+    }
+    // The penalties for the shortage and the surplus in the scenario
+    pub fn penalty(&self, scenario: usize) -> u64 {
+        let short: u64 = self.shortages(scenario).iter().zip(self.shortage.iter()).map(|(x, p)| x * p).sum();
+        let extra: u64 = self.surpluses(scenario).iter().zip(self.surplus.iter()).map(|(x, h)| x * h).sum();
+        short + extra
+    }
+
+    pub fn scenario_cost(&self, scenario: usize) -> u64 {
+        self.transport_cost() + self.penalty(scenario)
+    }
+
+    pub fn expected_cost(&self) -> f64 {
+        let weight: u64 = self.scenarios.iter().map(|s| s.0).sum();
+        let total: u64 = (0..self.scenarios.len()).map(|s| self.scenarios[s].0 * self.scenario_cost(s)).sum();
+        total as f64 / weight as f64
+    }
+
+    pub fn printstd(&self) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let shipped: Vec<String> = self.shipped().iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan(), &supply, &shipped);
+        table.add_row(Row::new(vec![Cell::new("Перевозка"), Cell::new(&format!("{}", self.transport_cost()))]));
+        table.printstd();
+
+        let mut table = Table::new();
+        table.add_row(Row::new(vec![Cell::new("Сценарий"), Cell::new("Вес"), Cell::new("Нехватка"), Cell::new("Излишек"), Cell::new("Стоимость")]));
+        for s in 0..self.scenarios.len() {
+            table.add_row(Row::new(vec![Cell::new(&format!("{}", s + 1)),
+                                        Cell::new(&format!("{}", self.scenarios[s].0)),
+                                        Cell::new(&format!("{}", self.shortages(s).iter().sum::<u64>())),
+                                        Cell::new(&format!("{}", self.surpluses(s).iter().sum::<u64>())),
+                                        Cell::new(&format!("{}", self.scenario_cost(s)))]));
+        }
+        table.add_row(Row::new(vec![Cell::new("Ожидаемая стоимость"), Cell::new(&format!("{:.2}", self.expected_cost()))]));
+        table.printstd();
+    }
+    // a is the supply, c is the cost of sending a unit, every scenario is its weight and the demand
+    // of the customers, p and h are the penalties of the customers for a unit of shortage and surplus
+    pub fn new(a: Vec<u64>, c: Vec<Vec<u64>>, scenarios: Vec<(u64, Vec<u64>)>, p: Vec<u64>, h: Vec<u64>) -> Result<Stochastic, Error> {
+        if scenarios.is_empty() || scenarios.iter().all(|s| s.0 == 0) {
+            return Err(NumOfScenarios);
+        }
+        let n = p.len();
+        if a.is_empty() || n == 0 || h.len() != n || scenarios.iter().any(|s| s.1.len() != n) {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if n != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        let weight: u64 = scenarios.iter().map(|s| s.0).sum();
+        // Every piece is (customer, size, marginal penalty multiplied by the weight)
+        let mut pieces = Vec::new();
+        for j in 0..n {
+            let mut levels: Vec<u64> = scenarios.iter().filter(|s| s.0 > 0).map(|s| s.1[j]).collect();
+            levels.push(0);
+            levels.sort();
+            levels.dedup();
+            for l in levels.windows(2) {
+                let over: u64 = scenarios.iter().filter(|s| s.1[j] <= l[0]).map(|s| s.0).sum();
+                let under: u64 = scenarios.iter().filter(|s| s.1[j] >= l[1]).map(|s| s.0).sum();
+                pieces.push((j, l[1] - l[0], (h[j] * over) as i64 - (p[j] * under) as i64));
+            }
+        }
+        let shift = pieces.iter().map(|&(_, _, m)| -m).max().unwrap_or(0).max(0);
+
+        let mut cost: Vec<Vec<u64>> = c.iter()
+                                       .map(|row| {
+                                           let mut row: Vec<u64> = pieces.iter().map(|&(j, _, m)| (row[j] as i64 * weight as i64 + m + shift) as u64).collect();
+                                           row.push(0);
+                                           row
+                                       })
+                                       .collect();
+        let mut unfilled = vec![shift as u64; pieces.len()];
+        unfilled.push(0);
+        cost.push(unfilled);
+
+        let sum_s: u64 = a.iter().sum();
+        let mut supply = a.clone();
+        supply.push(pieces.iter().map(|&(_, size, _)| size).sum());
+        let mut demand: Vec<u64> = pieces.iter().map(|&(_, size, _)| size).collect();
+        demand.push(sum_s);
+
+        Ok(Stochastic {
+            inner: Transportation::balanced(supply, demand, cost),
+            pieces: pieces.iter().map(|&(j, _, _)| j).collect(),
+            supply: a,
+            cost: c,
+            scenarios,
+            shortage: p,
+            surplus: h,
+        })
+    }
+}
@@ -0,0 +1,193 @@
+use prettytable::row::Row;
+use prettytable::cell::Cell;
+
+use super::{checked_prohibitive_cost, label, plan_table, CostSource, Transportation};
+use super::Error::{self, *};
+
+// The size of the original problem and the route behind every tier.
+// Each tier is an extra column and an extra row placed after the original ones.
+pub struct Tariffs {
+    rows: usize,
+    cols: usize,
+    supply: Vec<u64>,
+    demand: Vec<u64>,
+    tiers: Vec<(usize, usize)>,
+}
+
+impl Tariffs {
+    // Sum the direct route and its tiers
+    pub fn plan<C: CostSource>(&self, t: &Transportation<C>) -> Vec<Vec<u64>> {
+        let plan = t.plan();
+        let mut result: Vec<Vec<u64>> = plan.iter().take(self.rows).map(|row| row[..self.cols].to_vec()).collect();
+        for (k, &(i, j)) in self.tiers.iter().enumerate() {
+            result[i][j] += plan[i][self.cols + k];
+        }
+        result
+    }
+
+    pub fn printstd<C: CostSource>(&self, t: &Transportation<C>) {
+        let supply: Vec<String> = self.supply.iter().map(u64::to_string).collect();
+        let demand: Vec<String> = self.demand.iter().map(u64::to_string).collect();
+        let mut table = plan_table(&self.plan(t), &supply, &demand);
+        table.add_row(Row::new(vec![Cell::new("Общая стоимость"), Cell::new(&format!("{}", t.total_cost()))]));
+        table.printstd();
+    }
+    // The names of the rows and the columns of the balanced problem, the tiers are named after their routes
+    pub fn names(&self, suppliers: &[String], customers: &[String], shape: (usize, usize)) -> (Vec<String>, Vec<String>) {
+        let tier = |k: usize| {
+            let (i, j) = self.tiers[k];
+            format!("{}_{}_tier", label(suppliers, i, "A"), label(customers, j, "B"))
+        };
+        let rows = (0..shape.0).map(|i| match i {
+                                   i if i < self.rows => label(suppliers, i, "A"),
+                                   i if i < self.rows + self.tiers.len() => tier(i - self.rows),
+                                   _ => "dummy".to_string(),
+                               })
+                               .collect();
+        let cols = (0..shape.1).map(|j| match j {
+                                   j if j < self.cols => label(customers, j, "B"),
+                                   j if j < self.cols + self.tiers.len() => tier(j - self.cols),
+                                   _ => "dummy".to_string(),
+                               })
+                               .collect();
+        (rows, cols)
+    }
+    // The cells of the extra nodes with the prohibitive cost
+    pub fn forbidden<C: CostSource>(&self, t: &Transportation<C>) -> Vec<(usize, usize)> {
+        let max = (0..self.rows * self.cols).map(|n| t.cost_of(n / self.cols, n % self.cols)).max().unwrap_or(0);
+        let mut cells = Vec::new();
+        for i in 0..t.supply.len() {
+            for j in 0..t.demand.len() {
+                if (i >= self.rows || j >= self.cols) && t.cost_of(i, j) > max {
+                    cells.push((i, j));
+                }
+            }
+        }
+        cells
+    }
+}
+
+// The route (i, j) and its tiers (volume, cost)
+pub type RouteTariff = (usize, usize, Vec<(u64, u64)>);
+
+impl Transportation {
+    // The tariff of the route (i, j) is a list of tiers (volume, cost): the units up to the volume
+    // of the first tier cost its cost, the next ones up to the volume of the second tier cost
+    // the second cost and so on. The units above the last volume cost c[i][j].
+    // The costs must not decrease, so that the cheaper tiers are always filled first.
+    //
+    // Each tier becomes a pair of an extra customer and an extra supplier, both with the volume of
+    // the tier. The supplier i sends units to the extra customer at the cost of the tier and the extra
+    // supplier passes the same amount to the customer j, the rest of its volume goes back to the extra
+    // customer. All other routes of the extra nodes are forbidden.
+    pub fn with_tariffs(a: Vec<u64>, b: Vec<u64>, c: Vec<Vec<u64>>, tariffs: Vec<RouteTariff>) -> Result<Transportation, Error> {
+        let original = c.clone();
+        Transportation::tiered(a, b, c, tariffs, &original)
+    }
+    // The problem with tariffs where the prohibitive cost is taken from the costs `original`,
+    // so the routes of c may already hold such a cost
+    fn tiered(a: Vec<u64>, b: Vec<u64>, mut c: Vec<Vec<u64>>, tariffs: Vec<RouteTariff>, original: &[Vec<u64>]) -> Result<Transportation, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(NumOfSupOrCust);
+        }
+        if a.len() != c.len() {
+            return Err(NumOfRows);
+        }
+        for i in &c {
+            if b.len() != i.len() {
+                return Err(NumOfCols);
+            }
+        }
+
+        // (i, j, volume, cost)
+        let mut tiers = Vec::new();
+        for (n, &(i, j, ref tariff)) in tariffs.iter().enumerate() {
+            if i >= a.len() || j >= b.len() || tariffs[..n].iter().any(|t| t.0 == i && t.1 == j) {
+                return Err(Tariff);
+            }
+            let mut volume = 0;
+            let mut cost = 0;
+            for &(v, p) in tariff {
+                if v <= volume || p < cost {
+                    return Err(Tariff);
+                }
+                tiers.push((i, j, v - volume, p));
+                volume = v;
+                cost = p;
+            }
+            if c[i][j] < cost {
+                return Err(Tariff);
+            }
+        }
+
+        let rows = a.len();
+        let cols = b.len();
+        let m = checked_prohibitive_cost(original, a.iter().sum())?;
+        // The tiers add rows and columns to the paths of the potentials
+        if m.checked_mul((rows + cols + 2 * tiers.len() + 2) as u64).is_none_or(|x| x > i64::MAX as u64) {
+            return Err(Overflow);
+        }
+        for (i, row) in c.iter_mut().enumerate() {
+            row.extend(tiers.iter().map(|&(k, _, _, p)| if k == i { p } else { m }));
+        }
+        for (k, &(_, j, _, _)) in tiers.iter().enumerate() {
+            let mut row: Vec<u64> = (0..cols).map(|l| if l == j { 0 } else { m }).collect();
+            row.extend((0..tiers.len()).map(|l| if l == k { 0 } else { m }));
+            c.push(row);
+        }
+        let mut supply = a.clone();
+        supply.extend(tiers.iter().map(|t| t.2));
+        let mut demand = b.clone();
+        demand.extend(tiers.iter().map(|t| t.2));
+
+        let mut t = Transportation::new(supply, demand, c)?;
+        // The dummy must not pass through the tiers
+        for k in 0..tiers.len() {
+            if t.cost.cols() > cols + tiers.len() {
+                t.cost[rows + k][cols + tiers.len()] = m;
+            }
+            if t.cost.rows() > rows + tiers.len() {
+                t.cost[rows + tiers.len()][cols + k] = m;
+            }
+        }
+        t.tariffs = Some(Tariffs {
+            rows,
+            cols,
+            supply: a,
+            demand: b,
+            tiers: tiers.iter().map(|&(i, j, _, _)| (i, j)).collect(),
+        });
+        Ok(t)
+    }
+}
+
+// Solve the problem where the route (i, j) carries at most u[i][j] units.
+// Such a route is a single tier with a prohibitive cost above it.
+// Return the plan without the dummies or None if the capacities can't be kept.
+pub fn capacitated(a: &[u64], b: &[u64], c: &[Vec<u64>], u: &[Vec<u64>]) -> Result<Option<Vec<Vec<u64>>>, Error> {
+    let m = checked_prohibitive_cost(c, a.iter().sum())?;
+    let mut limited = c.to_vec();
+    let mut tariffs = Vec::new();
+    for (i, row) in limited.iter_mut().enumerate() {
+        for (j, cost) in row.iter_mut().enumerate() {
+            if u[i][j] < std::cmp::min(a[i], b[j]) {
+                if u[i][j] > 0 {
+                    tariffs.push((i, j, vec![(u[i][j], *cost)]));
+                }
+                *cost = m;
+            }
+        }
+    }
+
+    let mut t = Transportation::tiered(a.to_vec(), b.to_vec(), limited, tariffs, c)?;
+    t.potential_method();
+    let plan = t.original_plan();
+    for (i, row) in plan.iter().enumerate() {
+        for (j, &x) in row.iter().enumerate() {
+            if x > u[i][j] {
+                return Ok(None);
+            }
+        }
+    }
+    Ok(Some(plan))
+}
@@ -1,15 +1,19 @@
-use super::Transportation;
-use super::Direction;
+use super::{location_costs, parse_csv, parse_lp, parse_mps, Block, CandidateList, CostScaling, CostSource, Dantzig, Error, FacilityLocation, FirstNegative, FixedCharge, Generalized, LocationCosts, Matrix, Metric, MultiCommodity, MultiObjective, MultiPeriod, Parallel, Parametric, PotentialMethod, Pricing, Profit, Robust, Solid, Solver, SparseTransportation, SteepestEdge, Stochastic, SuccessiveShortestPath, Transportation, TwoStage};
+use super::{BasisPrices, Direction};
 
 fn init() -> Transportation {
     let a = vec![300, 250, 200];
     let b = vec![220, 150, 250, 180];
     let c = vec![vec![4, 5, 3, 6], vec![7, 2, 1, 5], vec![6, 1, 4, 2]];
-    let t = match Transportation::new(a, b, c) {
+    match Transportation::new(a, b, c) {
         Ok(v) => v,
-        Err(e) => panic!(e),
-    };
-    t
+        Err(e) => panic!("{}", e),
+    }
+}
+
+// The cells of the plan row by row, None for the free ones
+fn data(t: &Transportation) -> Vec<Option<u64>> {
+    (0..t.trans.rows()).flat_map(|i| (0..t.trans.cols()).map(move |j| t.trans.get(i, j))).collect()
 }
 
 #[test]
@@ -17,7 +21,7 @@ fn test_transportation_create() {
     let t = init();
     assert_eq!(t.supply.len(), 4);
     assert_eq!(t.demand.len(), 4);
-    assert_eq!(t.trans.data.len(), 16);
+    assert_eq!((t.trans.rows(), t.trans.cols()), (4, 4));
 }
 
 #[test]
@@ -26,16 +30,16 @@ fn test_least_cost_method() {
     let result = vec![Some(170), None, None, Some(130), None, None, Some(250), None, None,
                       Some(150), None, Some(50), Some(50), None, None, None];
     t.least_cost_method();
-    assert_eq!(t.trans.data, result);
+    assert_eq!(data(&t), result);
 }
 
 #[test]
 fn test_cycle_detection() {
     let mut t = init();
     t.least_cost_method();
-    assert_eq!(t.cycle_detection(0, 0)[0][0], Direction::None);
-    t.trans[0][1] = Some(0);
-    assert_eq!(t.cycle_detection(0, 1)[0][1], Direction::Left);
+    assert_eq!(t.cycle_detection(0, 0).get(0, 0), Direction::None);
+    t.trans.set(0, 1, Some(0));
+    assert_eq!(t.cycle_detection(0, 1).get(0, 1), Direction::Left);
 }
 
 #[test]
@@ -43,7 +47,7 @@ fn test_replenish() {
     let mut t = init();
     t.least_cost_method();
     t.replenish();
-    assert_eq!(t.trans[0][2], Some(0));
+    assert_eq!(t.trans.get(0, 2), Some(0));
 }
 
 #[test]
@@ -77,5 +81,766 @@ fn test_potential_method() {
     t.potential_method();
     let result = vec![Some(220), None, Some(80), None, None, Some(80), Some(170), None, None,
                       Some(70), None, Some(130), None, None, None, Some(50)];
-    assert_eq!(t.trans.data, result);
+    assert_eq!(data(&t), result);
+}
+
+#[test]
+fn test_degenerate() {
+    // The least cost method fills three cells, two more zeros complete the basis
+    let a = vec![10, 10, 10];
+    let b = vec![10, 10, 10];
+    let c = vec![vec![1, 5, 5], vec![5, 1, 5], vec![5, 5, 1]];
+    let mut t = Transportation::new(a, b, c).unwrap();
+    t.potential_method();
+    assert_eq!(t.trans.len(), 5);
+    assert_eq!(t.total_cost(), 30);
+
+    // The pivots move zero amounts, the entering cell must stay in the basis
+    let a = vec![3, 1, 3];
+    let b = vec![4, 3];
+    let c = vec![vec![2, 3], vec![4, 4], vec![4, 1]];
+    let mut t = Transportation::new(a, b, c).unwrap();
+    t.potential_method();
+    assert_eq!(t.trans.len(), 4);
+    assert!(t.check().is_none());
+    assert_eq!(t.total_cost(), 13);
+}
+
+#[test]
+fn test_with_ranges() {
+    // Both customers get only their minimum, the first supplier ships its minimum
+    let a = vec![(50, 100), (0, 80)];
+    let b = vec![(30, 60), (40, 120)];
+    let c = vec![vec![2, 9], vec![5, 3]];
+    let mut t = Transportation::with_ranges(a, b, c).unwrap();
+    t.potential_method();
+    let plan = t.original_plan();
+    assert_eq!(plan, vec![vec![50, 0], vec![0, 40]]);
+    assert_eq!(t.total_cost(), 220);
+}
+
+#[test]
+fn test_with_ranges_errors() {
+    let c = vec![vec![1, 2]];
+    match Transportation::with_ranges(vec![(5, 1)], vec![(0, 1), (0, 1)], c.clone()) {
+        Err(Error::Range) => {}
+        _ => panic!("expected Error::Range"),
+    }
+    match Transportation::with_ranges(vec![(0, 10)], vec![(6, 8), (6, 8)], c) {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+    // The prohibitive cost of the mandatory parts doesn't fit in i64
+    let c = vec![vec![1_000_000, 1], vec![1, 1_000_000]];
+    match Transportation::with_ranges(vec![(0, 10_000_000_000_000), (5, 5)], vec![(5, 5), (0, 10_000_000_000_000)], c.clone()) {
+        Err(Error::Overflow) => {}
+        _ => panic!("expected Error::Overflow"),
+    }
+    let mut t = Transportation::with_ranges(vec![(0, 100_000_000_000), (5, 5)], vec![(5, 5), (0, 100_000_000_000)], c).unwrap();
+    t.potential_method();
+    assert_eq!(t.original_plan(), vec![vec![0, 0], vec![5, 0]]);
+    assert_eq!(t.total_cost(), 5);
+}
+
+#[test]
+fn test_fixed_charge_heuristic() {
+    // The cheap routes are expensive to open
+    let a = vec![10, 10];
+    let b = vec![10, 10];
+    let c = vec![vec![1, 2], vec![2, 1]];
+    let f = vec![vec![100, 0], vec![0, 100]];
+    let mut t = FixedCharge::new(a, b, c, f).unwrap();
+    t.heuristic();
+    assert_eq!(t.fixed_cost(), 0);
+    assert_eq!(t.variable_cost(), 40);
+}
+
+#[test]
+fn test_fixed_charge_branch_and_bound() {
+    let a = vec![20, 15];
+    let b = vec![10, 12, 13];
+    let c = vec![vec![3, 1, 4], vec![2, 5, 1]];
+    let f = vec![vec![10, 40, 15], vec![30, 5, 20]];
+    let mut t = FixedCharge::new(a, b, c, f).unwrap();
+    t.branch_and_bound();
+    assert_eq!(t.total_cost(), 138);
+}
+
+#[test]
+fn test_with_tariffs() {
+    // The first 60 units of the route cost 5, the rest cost 7
+    let mut t = Transportation::with_tariffs(vec![100], vec![100], vec![vec![7]], vec![(0, 0, vec![(60, 5)])]).unwrap();
+    t.potential_method();
+    assert_eq!(t.original_plan(), vec![vec![100]]);
+    assert_eq!(t.total_cost(), 580);
+
+    // Above 30 units the first route gets more expensive than the second one
+    let a = vec![50, 50];
+    let b = vec![50, 40];
+    let c = vec![vec![6, 9], vec![5, 8]];
+    let mut t = Transportation::with_tariffs(a, b, c, vec![(0, 0, vec![(30, 1)])]).unwrap();
+    t.potential_method();
+    assert_eq!(t.original_plan(), vec![vec![30, 10], vec![20, 30]]);
+    assert_eq!(t.total_cost(), 30 + 90 + 100 + 240);
+}
+
+#[test]
+fn test_with_tariffs_errors() {
+    match Transportation::with_tariffs(vec![10], vec![10], vec![vec![3]], vec![(0, 0, vec![(5, 4)])]) {
+        Err(Error::Tariff) => {}
+        _ => panic!("expected Error::Tariff"),
+    }
+    match Transportation::with_tariffs(vec![10], vec![10], vec![vec![3]], vec![(0, 1, vec![(5, 1)])]) {
+        Err(Error::Tariff) => {}
+        _ => panic!("expected Error::Tariff"),
+    }
+}
+
+#[test]
+fn test_multi_commodity() {
+    // Both products prefer the diagonal routes, but they can't carry both
+    let a = vec![vec![10, 10], vec![10, 10]];
+    let b = vec![vec![10, 10], vec![10, 10]];
+    let c = vec![vec![vec![1, 3], vec![3, 1]], vec![vec![1, 5], vec![5, 1]]];
+    let u = vec![vec![12, 20], vec![20, 12]];
+    let mut t = MultiCommodity::new(a, b, c, u).unwrap();
+    t.solve().unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            assert!(t.plan(0)[i][j] + t.plan(1)[i][j] <= if i == j { 12 } else { 20 });
+        }
+    }
+    assert_eq!(t.plan(1), &[vec![10, 0], vec![0, 10]][..]);
+    assert_eq!(t.total_cost(), 2 * (2 + 8 * 3) + 20);
+    assert!(t.lower_bound() <= t.total_cost());
+
+    // The single route can't carry both products
+    let mut t = MultiCommodity::new(vec![vec![10], vec![10]], vec![vec![10], vec![10]], vec![vec![vec![1]], vec![vec![2]]], vec![vec![15]]).unwrap();
+    match t.solve() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+}
+
+#[test]
+fn test_multi_commodity_large_supplies() {
+    // The prohibitive cost of the capacities must not be fed back into the tiers
+    let n = 1_000_000_000;
+    let a = vec![vec![n, n], vec![n, n]];
+    let b = vec![vec![n, n], vec![n, n]];
+    let c = vec![vec![vec![1, 3], vec![3, 1]], vec![vec![1, 5], vec![5, 1]]];
+    let u = vec![vec![n + n / 5, 2 * n], vec![2 * n, n + n / 5]];
+    let mut t = MultiCommodity::new(a, b, c, u).unwrap();
+    t.solve().unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            assert!(t.plan(0)[i][j] + t.plan(1)[i][j] <= if i == j { n + n / 5 } else { 2 * n });
+        }
+    }
+    assert!(t.lower_bound() <= t.total_cost());
+}
+
+#[test]
+fn test_solid() {
+    // The trucks are cheaper but carry only 15 units
+    let a = vec![20, 10];
+    let b = vec![10, 20];
+    let e = vec![15, 100];
+    let c = vec![vec![vec![1, 4], vec![2, 6]], vec![vec![3, 5], vec![1, 3]]];
+    let mut t = Solid::new(a, b, e, c).unwrap();
+    t.solve().unwrap();
+    let trucks: u64 = t.plan().iter().flat_map(|row| row.iter()).map(|x| x[0]).sum();
+    assert_eq!(trucks, 15);
+    assert_eq!(t.total_cost(), 75);
+    assert!(t.lower_bound() <= t.total_cost());
+}
+
+#[test]
+fn test_multi_period() {
+    // The second period has no supply, so its demand is produced in advance
+    let a = vec![vec![30], vec![0]];
+    let b = vec![vec![10, 5], vec![10, 5]];
+    let c = vec![vec![2, 3]];
+    let mut t = MultiPeriod::new(a, b, c, vec![1], None).unwrap();
+    t.solve().unwrap();
+    assert_eq!(t.shipments(0), vec![vec![10, 5]]);
+    assert_eq!(t.shipments(1), vec![vec![10, 5]]);
+    assert_eq!(t.inventory(0), vec![15]);
+    assert_eq!(t.holding_cost(), 15);
+    assert_eq!(t.total_cost(), 2 * (20 + 15) + 15);
+}
+
+#[test]
+fn test_multi_period_backorders() {
+    let a = vec![vec![0], vec![20]];
+    let b = vec![vec![10], vec![10]];
+    let c = vec![vec![1]];
+    let mut t = MultiPeriod::new(a.clone(), b.clone(), c.clone(), vec![1], None).unwrap();
+    match t.solve() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+    let mut t = MultiPeriod::new(a, b, c, vec![1], Some(vec![4])).unwrap();
+    t.solve().unwrap();
+    assert_eq!(t.backorders(0), vec![10]);
+    assert_eq!(t.backorder_cost(), 40);
+    assert_eq!(t.total_cost(), 60);
+}
+
+#[test]
+fn test_two_stage() {
+    // The cheap warehouse passes only 30 units
+    let a = vec![40, 30];
+    let b = vec![20, 40];
+    let w = vec![30, 100];
+    let c1 = vec![vec![1, 3], vec![2, 2]];
+    let c2 = vec![vec![1, 1], vec![2, 3]];
+    let mut t = TwoStage::new(a, b, w, c1, c2).unwrap();
+    t.solve().unwrap();
+    assert_eq!(t.throughput(), vec![30, 30]);
+    assert_eq!(t.first_stage(), vec![vec![30, 0], vec![0, 30]]);
+    assert_eq!(t.first_stage_cost(), 30 + 30 * 2);
+    assert_eq!(t.second_stage_cost(), 30 + 20 * 2 + 10 * 3);
+
+    let mut t = TwoStage::new(vec![50], vec![50], vec![20, 20], vec![vec![1, 1]], vec![vec![1], vec![1]]).unwrap();
+    match t.solve() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+}
+
+#[test]
+fn test_facility_location() {
+    // The big depot is too expensive to open, two small ones are enough
+    let s = vec![100, 40, 40];
+    let f = vec![500, 100, 100];
+    let b = vec![30, 40];
+    let c = vec![vec![1, 1], vec![2, 5], vec![5, 2]];
+    let mut t = FacilityLocation::new(s.clone(), f.clone(), b.clone(), c.clone()).unwrap();
+    t.heuristic().unwrap();
+    assert_eq!(t.opened(), vec![1, 2]);
+    assert_eq!(t.total_cost(), 200 + 60 + 80);
+
+    let mut t = FacilityLocation::new(s, f, b, c).unwrap();
+    t.branch_and_bound().unwrap();
+    assert_eq!(t.opening_cost(), 200);
+    assert_eq!(t.transport_cost(), 140);
+
+    let mut t = FacilityLocation::new(vec![10], vec![1], vec![20], vec![vec![1]]).unwrap();
+    match t.heuristic() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+}
+
+#[test]
+fn test_profit() {
+    // The second customer pays less than it costs to serve it
+    let a = vec![50, 30];
+    let p = vec![2, 4];
+    let b = vec![40, 60];
+    let r = vec![10, 5];
+    let c = vec![vec![1, 4], vec![3, 2]];
+    let mut t = Profit::new(a, p, b, r, c).unwrap();
+    t.solve();
+    assert_eq!(t.plan(), vec![vec![40, 0], vec![0, 0]]);
+    assert_eq!(t.profit(), 40 * 7);
+    assert_eq!(t.revenue(), 400);
+}
+
+#[test]
+fn test_generalized() {
+    // Without losses it is the usual transportation problem
+    let a = vec![300, 250, 200];
+    let b = vec![220, 150, 200, 180];
+    let c = vec![vec![4, 5, 3, 6], vec![7, 2, 1, 5], vec![6, 1, 4, 2]];
+    let mut t = Transportation::new(a.clone(), b.clone(), c.clone()).unwrap();
+    t.potential_method();
+    let mut g = Generalized::new(a, b, c, vec![vec![1.0; 4]; 3]).unwrap();
+    g.solve().unwrap();
+    assert!((g.total_cost() - t.total_cost() as f64).abs() < 1e-6);
+
+    // Half of the goods are lost on the cheap route
+    let mut g = Generalized::new(vec![100, 100], vec![50], vec![vec![1], vec![3]], vec![vec![0.5], vec![1.0]]).unwrap();
+    g.solve().unwrap();
+    assert!((g.plan()[0][0] - 100.0).abs() < 1e-6);
+    assert!((g.total_cost() - 100.0).abs() < 1e-6);
+    let mut g = Generalized::new(vec![100, 100], vec![50], vec![vec![1], vec![1]], vec![vec![0.5], vec![1.0]]).unwrap();
+    g.solve().unwrap();
+    assert!((g.plan()[1][0] - 50.0).abs() < 1e-6);
+
+    let mut g = Generalized::new(vec![10], vec![50], vec![vec![1]], vec![vec![2.0]]).unwrap();
+    match g.solve() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+    assert!(Generalized::new(vec![10], vec![5], vec![vec![1]], vec![vec![0.0]]).is_err());
+}
+
+#[test]
+fn test_multi_objective() {
+    let a = vec![10, 10];
+    let b = vec![10, 10];
+    let c1 = vec![vec![1, 2], vec![2, 1]];
+    let c2 = vec![vec![3, 1], vec![1, 3]];
+    let diagonal = vec![vec![10, 0], vec![0, 10]];
+    let crosswise = vec![vec![0, 10], vec![10, 0]];
+    let t = MultiObjective::new(a.clone(), b.clone(), vec![c1.clone(), c2.clone()]).unwrap();
+    assert_eq!(t.weighted_sum(&[3, 1]).unwrap(), diagonal);
+    assert_eq!(t.weighted_sum(&[1, 3]).unwrap(), crosswise);
+    assert_eq!(t.values(&diagonal), vec![20, 60]);
+    assert_eq!(t.lexicographic(&[1, 0]), crosswise);
+    assert_eq!(t.pareto(10).unwrap(), vec![diagonal, crosswise.clone()]);
+    match t.weighted_sum(&[u64::MAX, 1]) {
+        Err(Error::Overflow) => {}
+        _ => panic!("expected Error::Overflow"),
+    }
+
+    // The first objective doesn't tell the plans apart
+    let t = MultiObjective::new(a, b, vec![vec![vec![1, 1], vec![1, 1]], c2]).unwrap();
+    assert_eq!(t.lexicographic(&[0, 1]), crosswise);
+    assert_eq!(t.pareto(4).unwrap(), vec![crosswise]);
+}
+
+#[test]
+fn test_parametric() {
+    // The surcharge makes the first supplier more expensive than the second one at t = 2
+    let mut t = Parametric::new(vec![10, 10], vec![10], vec![vec![1], vec![5]], vec![vec![2], vec![0]]).unwrap();
+    t.analyze((0, 1), (5, 1)).unwrap();
+    let intervals = t.intervals();
+    assert_eq!(intervals.len(), 2);
+    assert_eq!(intervals[0], (0.0, 2.0, vec![vec![10], vec![0]]));
+    assert_eq!(intervals[1], (2.0, 5.0, vec![vec![0], vec![10]]));
+    assert_eq!(t.cost(&intervals[0].2), (10, 20));
+
+    t.analyze((3, 2), (9, 4)).unwrap();
+    assert_eq!(t.intervals(), &[(1.5, 2.0, vec![vec![10], vec![0]]), (2.0, 2.25, vec![vec![0], vec![10]])][..]);
+    for &(from, to) in &[((5, 1), (0, 1)), ((0, 0), (5, 1))] {
+        match t.analyze(from, to) {
+            Err(Error::Parameter) => {}
+            _ => panic!("expected Error::Parameter"),
+        }
+    }
+}
+
+#[test]
+fn test_parametric_degenerate() {
+    // Every basis has a zero cell, the plans swap at t = 1
+    let c = vec![vec![1, 2], vec![2, 1]];
+    let d = vec![vec![1, 0], vec![0, 1]];
+    let mut t = Parametric::new(vec![5, 5], vec![5, 5], c, d).unwrap();
+    t.analyze((0, 1), (3, 1)).unwrap();
+    let intervals = t.intervals();
+    assert_eq!(intervals.len(), 2);
+    assert_eq!(intervals[0], (0.0, 1.0, vec![vec![5, 0], vec![0, 5]]));
+    assert_eq!(intervals[1], (1.0, 3.0, vec![vec![0, 5], vec![5, 0]]));
+}
+
+#[test]
+fn test_robust() {
+    // The first route may be free or the most expensive one
+    let low = vec![vec![0], vec![4]];
+    let high = vec![vec![9], vec![6]];
+    let mut t = Robust::new(vec![10, 10], vec![10], low.clone(), high.clone()).unwrap();
+    t.worst_case();
+    assert_eq!(t.plan(), &[vec![0], vec![10]]);
+    assert_eq!(t.worst_cost(), 60);
+    assert_eq!(t.regret_bound(), 60);
+    t.minimax_regret();
+    assert_eq!(t.plan(), &[vec![10], vec![0]]);
+    assert_eq!(t.best_cost(), 0);
+    assert_eq!(t.regret_bound(), 50);
+
+    assert!(Robust::new(vec![10], vec![10], high, low).is_err());
+}
+
+#[test]
+fn test_stochastic() {
+    let scenarios = vec![(1, vec![10]), (1, vec![30])];
+    let mut t = Stochastic::new(vec![100], vec![vec![1]], scenarios.clone(), vec![10], vec![2]).unwrap();
+    t.solve();
+    assert_eq!(t.plan(), vec![vec![30]]);
+    assert_eq!(t.surpluses(0), vec![20]);
+    assert_eq!(t.scenario_cost(0), 30 + 20 * 2);
+    assert_eq!(t.scenario_cost(1), 30);
+    assert_eq!(t.expected_cost(), 50.0);
+
+    // Shipping more than the lower demand doesn't pay off
+    let mut t = Stochastic::new(vec![100], vec![vec![5]], scenarios, vec![10], vec![2]).unwrap();
+    t.solve();
+    assert_eq!(t.shipped(), vec![10]);
+    assert_eq!(t.shortages(1), vec![20]);
+    assert_eq!(t.expected_cost(), 150.0);
+}
+
+#[test]
+fn test_with_locations() {
+    assert_eq!(Metric::Euclidean.distance((0.0, 0.0), (3.0, 4.0)), 5.0);
+    assert_eq!(Metric::Manhattan.distance((0.0, 0.0), (3.0, -4.0)), 7.0);
+    // A degree of the meridian is about 111 km
+    assert_eq!(Metric::Haversine.distance((55.0, 37.0), (56.0, 37.0)).round(), 111.0);
+
+    let suppliers = vec![(0.0, 0.0, 30, 1), (10.0, 0.0, 20, 0)];
+    let customers = vec![(3.0, 4.0, 25, 0), (10.0, 5.0, 25, 2)];
+    assert_eq!(location_costs(&suppliers, &customers, Metric::Euclidean, 2.0), vec![vec![11, 25], vec![16, 12]]);
+    for &rate in &[-1.0, f64::NAN] {
+        match Transportation::with_locations(suppliers.clone(), customers.clone(), Metric::Euclidean, rate) {
+            Err(Error::Rate) => {}
+            _ => panic!("expected Error::Rate"),
+        }
+    }
+    let mut t = Transportation::with_locations(suppliers, customers, Metric::Euclidean, 2.0).unwrap();
+    t.potential_method();
+    assert_eq!(t.total_cost(), 25 * 11 + 5 * 25 + 20 * 12);
+}
+
+#[test]
+fn test_with_source() {
+    let suppliers = vec![(0.0, 0.0, 30, 1), (10.0, 0.0, 20, 0)];
+    let customers = vec![(3.0, 4.0, 25, 0), (10.0, 5.0, 25, 2)];
+    let mut dense = Transportation::with_locations(suppliers.clone(), customers.clone(), Metric::Euclidean, 2.0).unwrap();
+    dense.potential_method();
+    let source = LocationCosts::new(suppliers, customers, Metric::Euclidean, 2.0);
+    let mut t = Transportation::with_source(vec![30, 20], vec![25, 25], source).unwrap();
+    t.potential_method();
+    assert_eq!(t.plan(), dense.plan());
+    assert_eq!(t.total_cost(), dense.total_cost());
+
+    // The dummy customer isn't known to the source
+    let source = LocationCosts::new(vec![(0.0, 0.0, 30, 0)], vec![(3.0, 4.0, 20, 0)], Metric::Manhattan, 1.0);
+    let mut t = Transportation::with_source(vec![30], vec![20], source).unwrap();
+    t.potential_method();
+    assert_eq!(t.plan(), vec![vec![20, 10]]);
+    assert_eq!(t.total_cost(), 140);
+    assert!(Transportation::with_source(vec![30, 20], vec![20], LocationCosts::new(Vec::new(), Vec::new(), Metric::Euclidean, 1.0)).is_err());
+
+    // The matrix built by the caller is a source too
+    let mut t = Transportation::with_source(vec![30, 20], vec![25, 25], Matrix::from_rows(vec![vec![1, 2], vec![3, 1]])).unwrap();
+    t.potential_method();
+    assert_eq!(t.total_cost(), 55);
+
+    // The source that counts the queries isn't Sync
+    struct Counted(std::cell::Cell<usize>, Matrix<u64>);
+    impl CostSource for Counted {
+        fn shape(&self) -> (usize, usize) {
+            self.1.shape()
+        }
+
+        fn cost(&self, i: usize, j: usize) -> u64 {
+            self.0.set(self.0.get() + 1);
+            self.1.cost(i, j)
+        }
+    }
+    let source = Counted(std::cell::Cell::new(0), Matrix::from_rows(vec![vec![1, 2], vec![3, 1]]));
+    let mut t = Transportation::with_source(vec![30, 20], vec![25, 25], source).unwrap();
+    t.potential_method();
+    assert_eq!(t.total_cost(), 55);
+    assert!(t.cost.0.get() > 0);
+    // Only the parallel rule needs Sync
+    let source = Counted(std::cell::Cell::new(0), Matrix::from_rows(vec![vec![1, 2], vec![3, 1]]));
+    let mut t = Transportation::with_source(vec![30, 20], vec![25, 25], source).unwrap();
+    t.potential_method_with(&mut Dantzig);
+    assert_eq!(t.total_cost(), 55);
+
+    // A million suppliers and customers: only the basic cells are kept
+    let places: Vec<_> = (0..1_000_000).map(|k| (k as f64, 0.0, 1, 0)).collect();
+    let source = LocationCosts::new(places.clone(), places, Metric::Manhattan, 1.0);
+    let t = Transportation::with_source(vec![1; 1_000_000], vec![1; 1_000_000], source).unwrap();
+    assert_eq!(t.trans.len(), 0);
+}
+
+#[test]
+fn test_sparse() {
+    // The same problem as init() with all routes allowed
+    let c = [[4, 5, 3, 6], [7, 2, 1, 5], [6, 1, 4, 2]];
+    let routes = (0..3).flat_map(|i| (0..4).map(move |j| (i, j, c[i][j]))).collect();
+    let mut t = SparseTransportation::new(vec![300, 250, 200], vec![220, 150, 250, 180], routes).unwrap();
+    t.potential_method().unwrap();
+    let mut dense = init();
+    dense.potential_method();
+    assert_eq!(t.total_cost(), dense.total_cost());
+
+    // The first supplier can't reach the second customer
+    let routes = vec![(0, 0, 1), (1, 0, 3), (1, 1, 2)];
+    let mut t = SparseTransportation::new(vec![20, 20], vec![10, 20], routes).unwrap();
+    t.potential_method().unwrap();
+    assert_eq!(t.plan(), vec![(0, 0, 10), (1, 1, 20)]);
+    assert_eq!(t.total_cost(), 50);
+
+    let mut t = SparseTransportation::new(vec![20, 20], vec![10, 30], vec![(0, 0, 1), (1, 1, 2)]).unwrap();
+    match t.potential_method() {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+    let mut t = SparseTransportation::new(vec![20, 20], vec![10, 30], vec![(0, 0, u64::MAX / 4), (1, 1, 2)]).unwrap();
+    match t.potential_method() {
+        Err(Error::Overflow) => {}
+        _ => panic!("expected Error::Overflow"),
+    }
+    assert!(SparseTransportation::new(vec![20], vec![20], vec![(0, 1, 1)]).is_err());
+    assert!(SparseTransportation::new(vec![20], vec![20], vec![(0, 0, 1), (0, 0, 2)]).is_err());
+}
+
+#[test]
+fn test_pricing() {
+    let mut t = init();
+    t.potential_method();
+    let result = data(&t);
+    let z = t.total_cost();
+
+    let mut rules: Vec<Box<dyn Pricing>> = vec![Box::new(Dantzig),
+                                                Box::new(FirstNegative::default()),
+                                                Box::new(CandidateList::new(3)),
+                                                Box::new(Block::new(4)),
+                                                Box::new(SteepestEdge::new(3))];
+    for rule in rules.iter_mut() {
+        let mut t = init();
+        let (iterations, _) = t.potential_method_with(&mut **rule);
+        assert!(iterations > 0);
+        assert_eq!(t.total_cost(), z);
+    }
+
+    // The most negative difference is the rule of potential_method
+    let mut t = init();
+    t.potential_method_with(&mut Dantzig);
+    assert_eq!(data(&t), result);
+}
+
+#[test]
+fn test_parallel_pricing() {
+    let a: Vec<u64> = (0..20).map(|i| 10 + i * 3 % 7).collect();
+    let b: Vec<u64> = (0..30).map(|j| 5 + j * 5 % 11).collect();
+    let c: Vec<Vec<u64>> = (0..20).map(|i| (0..30).map(|j| (i * 7 + j * 13) % 17).collect()).collect();
+    let mut serial = Transportation::new(a.clone(), b.clone(), c.clone()).unwrap();
+    serial.potential_method();
+    for threads in 1..5 {
+        let mut t = Transportation::new(a.clone(), b.clone(), c.clone()).unwrap();
+        t.potential_method_parallel(&mut Parallel::new(threads));
+        assert_eq!(data(&t), data(&serial));
+    }
+
+    // The problem is large enough for the threads
+    let a: Vec<u64> = (0..300).map(|i| 10 + i * 3 % 7).collect();
+    let b: Vec<u64> = (0..300).map(|j| 5 + j * 5 % 11).collect();
+    let c: Vec<Vec<u64>> = (0..300).map(|i| (0..300).map(|j| (i * 7 + j * 13) % 17).collect()).collect();
+    let mut t = Transportation::new(a, b, c).unwrap();
+    t.least_cost_method();
+    t.replenish();
+    let (u, v) = t.calculation_of_potentials();
+    let prices = BasisPrices { t: &t, u, v };
+    assert_eq!(Parallel::new(4).entering(&prices), Dantzig.entering(&prices));
+}
+
+#[test]
+fn test_solvers() {
+    let mut t = init();
+    t.potential_method();
+    let z = t.total_cost();
+
+    let solvers: Vec<Box<dyn Solver>> = vec![Box::new(PotentialMethod), Box::new(SuccessiveShortestPath), Box::new(CostScaling)];
+    for solver in &solvers {
+        let mut t = init();
+        // The plan of the solver is optimal before the basis is completed
+        let plan = solver.solve(&t);
+        for (i, row) in plan.iter().enumerate() {
+            assert_eq!(row.iter().sum::<u64>(), t.supply[i]);
+        }
+        let raw: u64 = (0..plan.len()).map(|i| (0..plan[i].len()).map(|j| plan[i][j] * t.cost[i][j]).sum::<u64>()).sum();
+        assert_eq!(raw, z);
+        t.solve_with(&**solver).unwrap();
+        assert_eq!(t.total_cost(), z);
+        assert_eq!(t.trans.len(), t.supply.len() + t.demand.len() - 1);
+        assert!(t.check().is_none());
+    }
+
+    // The least cost method isn't optimal here
+    struct LeastCost;
+    impl Solver for LeastCost {
+        fn solve(&self, t: &Transportation) -> Vec<Vec<u64>> {
+            let mut t = Transportation::new(t.supply.clone(), t.demand.clone(), (0..t.cost.rows()).map(|i| t.cost[i].to_vec()).collect()).unwrap();
+            t.least_cost_method();
+            t.plan()
+        }
+    }
+    match init().solve_with(&LeastCost) {
+        Err(Error::NotOptimal) => {}
+        _ => panic!("expected Error::NotOptimal"),
+    }
+}
+
+#[test]
+fn test_check_with_lp() {
+    let mut t = init();
+    let (_, same) = t.check_with_lp().unwrap();
+    assert!(!same);
+    t.potential_method();
+    let (z, same) = t.check_with_lp().unwrap();
+    assert!(same);
+    assert_eq!(z.round() as u64, t.total_cost());
+
+    // The ranges are kept by the prohibitive costs of the balanced problem
+    let mut t = Transportation::with_ranges(vec![(10, 20), (0, 30)], vec![(15, 25), (5, 10)], vec![vec![4, 1], vec![2, 3]]).unwrap();
+    t.potential_method();
+    assert!(t.check_with_lp().unwrap().1);
+}
+
+#[test]
+fn test_export() {
+    let t = Transportation::new(vec![30, 20], vec![25, 15], vec![vec![4, 1], vec![2, 3]]).unwrap();
+    let lp = t.to_lp(&["Moscow".to_string(), "St Petersburg".to_string()], &["Kazan".to_string()]);
+    assert!(lp.contains(" cost: 4 x_Moscow_Kazan + 1 x_Moscow_B2 + 0 x_Moscow_dummy + 2 x_St_Petersburg_Kazan"));
+    assert!(lp.contains(" s_Moscow: x_Moscow_Kazan + x_Moscow_B2 + x_Moscow_dummy = 30\n"));
+    assert!(lp.contains(" d_dummy: x_Moscow_dummy + x_St_Petersburg_dummy = 10\n"));
+    assert!(!lp.contains("Bounds"));
+
+    let mps = t.to_mps(&[], &[]);
+    assert!(mps.contains(" x_A2_B1 cost 2 s_A2 1\n x_A2_B1 d_B1 1\n"));
+    assert!(mps.contains(" RHS d_dummy 10\n"));
+    assert!(mps.ends_with("ENDATA\n"));
+
+    // The mandatory part of the first supplier can't be left with the dummy customer
+    let t = Transportation::with_ranges(vec![(10, 20), (0, 30)], vec![(15, 25), (5, 10)], vec![vec![4, 1], vec![2, 3]]).unwrap();
+    assert!(t.to_lp(&[], &[]).contains("Bounds\n x_A1_dummy = 0\n"));
+    assert!(t.to_mps(&[], &[]).contains("BOUNDS\n FX BND x_A1_dummy 0\n"));
+}
+
+#[test]
+fn test_import() {
+    let lp = "\\ Two plants and two markets\n\
+              Minimize\n obj: 2 x11 + 3 x12 + x21 + 4 x22\n\
+              Subject To\n plant1: x11 + x12 <= 10\n plant2: x21 + x22 <= 20\n\
+              market1: x11 + x21 >= 5\n market2: x12 + x22 >= 7\n\
+              Bounds\n 0 <= x21 <= inf\n\
+              End\n";
+    let (mut t, suppliers, customers) = parse_lp(lp).unwrap();
+    assert_eq!(suppliers, vec!["plant1", "plant2"]);
+    assert_eq!(customers, vec!["market1", "market2"]);
+    t.potential_method();
+    assert_eq!(t.total_cost(), 5 + 7 * 3);
+
+    // The exported problem is read back with its labels
+    let mut t = init();
+    let labels = vec!["Moscow".to_string()];
+    let (mut lp, suppliers, _) = parse_lp(&t.to_lp(&labels, &[])).unwrap();
+    let (mut mps, _, customers) = parse_mps(&t.to_mps(&[], &labels)).unwrap();
+    assert_eq!(suppliers[0], "Moscow");
+    assert_eq!(customers[0], "Moscow");
+    t.potential_method();
+    lp.potential_method();
+    mps.potential_method();
+    assert_eq!(lp.total_cost(), t.total_cost());
+    assert_eq!(mps.total_cost(), t.total_cost());
+
+    // The demand of d2 can only be met through the missing route x12
+    match parse_lp("min\n x11 + x21 + x22\nst\n s1: x11 = 10\n s2: x21 + x22 = 10\n d1: x11 + x21 = 5\n d2: x22 = 15\nend\n") {
+        Err(Error::Infeasible) => {}
+        _ => panic!("expected Error::Infeasible"),
+    }
+    // The route fixed at zero stays unused and fixed after the round trip of the original problem
+    let lp = "min\n x11 + 2 x12 + 3 x21 + x22\nst\n s1: x11 + x12 <= 10\n s2: x21 + x22 <= 10\n\
+              d1: x11 + x21 >= 10\n d2: x12 + x22 >= 5\nbounds\n x11 = 0\nend\n";
+    let (mut t, suppliers, customers) = parse_lp(lp).unwrap();
+    t.potential_method();
+    assert_eq!(t.original_plan(), vec![vec![0, 5], vec![10, 0]]);
+    let (mut lp, _, _) = parse_lp(&t.to_lp(&suppliers, &customers)).unwrap();
+    let (mut mps, _, _) = parse_mps(&t.to_mps(&suppliers, &customers)).unwrap();
+    let text = t.to_lp(&suppliers, &customers);
+    assert!(text.contains(" s_s1: x_s1_d1 + x_s1_d2 <= 10\n"));
+    assert!(text.contains(" d_d2: x_s1_d2 + x_s2_d2 >= 5\n"));
+    assert!(text.contains("Bounds\n x_s1_d1 = 0\n"));
+    assert!(!text.contains("dummy"));
+    assert_eq!(lp.to_lp(&suppliers, &customers), text);
+    assert!(t.to_mps(&suppliers, &customers).contains(" L s_s1\n L s_s2\n G d_d1\n G d_d2\n"));
+    assert!(lp.forbidden.contains(&(0, 0)));
+    assert!(mps.forbidden.contains(&(0, 0)));
+    lp.potential_method();
+    mps.potential_method();
+    assert_eq!(lp.original_plan()[0][0], 0);
+    assert_eq!(lp.total_cost(), t.total_cost());
+    assert_eq!(mps.total_cost(), t.total_cost());
+
+    // The second row has a coefficient other than 1
+    match parse_lp("min\n x + y\nst\n c1: x + y = 1\n c2: 2 x = 1\n c3: y = 0\nend\n") {
+        Err(Error::NotTransportation) => {}
+        _ => panic!("expected Error::NotTransportation"),
+    }
+    match parse_mps("NAME bad\nROWS\n N cost\n E r1\nCOLUMNS\n x cost 1 r9 1\nENDATA\n") {
+        Err(Error::Parse) => {}
+        _ => panic!("expected Error::Parse"),
+    }
+}
+
+#[test]
+fn test_csv() {
+    let text = ",Kaluga,\"Ryazan, east\",supply\nMoscow,1,2,40\nTula,3,1,20\ndemand,25,25,\n";
+    let (mut t, suppliers, customers) = parse_csv(text).unwrap();
+    assert_eq!(suppliers, vec!["Moscow", "Tula"]);
+    assert_eq!(customers, vec!["Kaluga", "Ryazan, east"]);
+    t.potential_method();
+    assert_eq!(t.total_cost(), 55);
+    // The dummy customer is left out, the supply column holds the amounts shipped
+    let plan = t.to_csv(&suppliers, &customers);
+    assert_eq!(plan, ",Kaluga,\"Ryazan, east\",supply\nMoscow,25,5,30\nTula,0,20,20\ndemand,25,25,\n");
+
+    match parse_csv(",Kaluga,Ryazan\nMoscow,1,2\ndemand,25,25\n") {
+        Err(Error::Csv) => {}
+        _ => panic!("expected Error::Csv"),
+    }
+    match parse_csv(",Kaluga,supply,supply\nMoscow,1,2,3\ndemand,2,,\n") {
+        Err(Error::Csv) => {}
+        _ => panic!("expected Error::Csv"),
+    }
+
+    // A quoted name may span lines and is written back in quotes
+    let text = ",\"Kaluga\r\nnorth\",Ryazan,supply\r\n\r\nMoscow,1,2,40\r\ndemand,10,30,\r\n";
+    let (mut t, suppliers, customers) = parse_csv(text).unwrap();
+    assert_eq!(customers, vec!["Kaluga\r\nnorth", "Ryazan"]);
+    t.potential_method();
+    assert_eq!(t.total_cost(), 70);
+    let (_, _, again) = parse_csv(&t.to_csv(&suppliers, &customers)).unwrap();
+    assert_eq!(again, customers);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    use super::{JsonProblem, JsonSolution};
+
+    let text = r#"{"supply": [300, 250, 200], "demand": [220, 150, 250, 180],
+                   "cost": [[4, 5, 3, 6], [7, 2, 1, 5], [6, 1, 4, 2]],
+                   "options": {"pricing": "dantzig"}}"#;
+    let p = JsonProblem::from_json(text).unwrap();
+    assert_eq!(p.options.pricing.as_deref(), Some("dantzig"));
+    assert_eq!(JsonProblem::from_json(&p.to_json()).unwrap(), p);
+
+    let mut t = p.transportation().unwrap();
+    let (iterations, _) = t.potential_method_with(&mut Dantzig);
+    let mut expected = init();
+    expected.potential_method();
+    let s = JsonSolution::new(&t, Some(iterations), &[], &[]);
+    assert_eq!(s.cost, expected.total_cost());
+    assert_eq!(s.plan.len(), 3);
+    // The potentials of the dummy supplier are named
+    assert_eq!(s.potentials.rows, vec!["A1", "A2", "A3", "dummy"]);
+    assert_eq!(s.potentials.cols, vec!["B1", "B2", "B3", "B4"]);
+    assert_eq!(s.potentials.u.len(), 4);
+    assert_eq!(JsonSolution::from_json(&s.to_json()).unwrap(), s);
+
+    // The ranges split the suppliers and the customers into parts
+    let mut t = Transportation::with_ranges(vec![(10, 20), (0, 30)], vec![(15, 25), (5, 10)], vec![vec![4, 1], vec![2, 3]]).unwrap();
+    t.potential_method();
+    let s = JsonSolution::new(&t, None, &[], &[]);
+    assert_eq!(s.potentials.rows, vec!["A1", "A1", "A2", "dummy"]);
+    assert_eq!((s.potentials.u.len(), s.potentials.v.len()), (s.potentials.rows.len(), s.potentials.cols.len()));
+
+    // The labels don't match the suppliers
+    let mut q = p.clone();
+    q.suppliers = vec!["Moscow".to_string()];
+    match q.transportation() {
+        Err(Error::NumOfLabels) => {}
+        _ => panic!("expected Error::NumOfLabels"),
+    }
+    // The message of the JSON parser is kept
+    let err = JsonProblem::from_json("{\"supply\": [1]}").unwrap_err();
+    assert!(err.to_string().contains("missing field `demand`"));
 }